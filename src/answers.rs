@@ -0,0 +1,164 @@
+use crate::common::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Known answers for each day and part, as stored in the answers file.
+///
+/// Every line of the file has the form `DAY PART VALUE`, for example `03 A 860`. Newlines and
+/// backslashes in the value are escaped as `\n` and `\\`. Empty lines and lines starting with
+/// `#` are ignored.
+#[derive(Debug, Default, Clone)]
+pub struct Expectations {
+    entries: BTreeMap<(usize, String), String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+        found: Option<String>,
+    },
+    Missing {
+        found: String,
+    },
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail {
+                expected,
+                found: Some(found),
+            } => write!(f, "FAIL (expected {:?}, found {:?})", expected, found),
+            Verdict::Fail {
+                expected,
+                found: None,
+            } => write!(f, "FAIL (expected {:?}, found nothing)", expected),
+            Verdict::Missing { found } => write!(f, "missing (found {:?})", found),
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> Result<String> {
+    let mut output = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => output.push('\n'),
+            Some('\\') => output.push('\\'),
+            other => bail!("invalid escape sequence: \\{}", other.unwrap_or(' ')),
+        }
+    }
+
+    Ok(output)
+}
+
+impl Expectations {
+    /// Loads the answers file at `path`. A file that does not exist yet has no answers.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(default()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+
+        let mut entries = BTreeMap::new();
+
+        for (index, line) in enumerate(content.lines()) {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts = line.splitn(3, ' ').collect::<Vec<_>>();
+            if parts.len() != 3 {
+                bail!(
+                    "{}:{}: expected `DAY PART VALUE`",
+                    path.display(),
+                    index + 1
+                );
+            }
+
+            let day = parts[0]
+                .parse::<usize>()
+                .with_context(|| format!("{}:{}: invalid day", path.display(), index + 1))?;
+            let value =
+                unescape(parts[2]).with_context(|| format!("{}:{}", path.display(), index + 1))?;
+
+            entries.insert((day, parts[1].to_string()), value);
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn save(&self, path: &Path) -> Result {
+        let mut content = String::from("# day part answer\n");
+
+        for ((day, part), value) in &self.entries {
+            content += &format!("{:02} {} {}\n", day, part, escape(value));
+        }
+
+        fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Replaces all answers of `day` by the given answers.
+    pub fn bless(&mut self, day: usize, answers: &[Answer]) {
+        self.entries.retain(|(d, _), _| *d != day);
+
+        for answer in answers {
+            let key = (day, answer.part.clone());
+            self.entries.insert(key, answer.value.clone());
+        }
+    }
+
+    /// Compares the answers reported for `day` against the known answers. Parts that have a
+    /// known answer but were not reported are failures.
+    pub fn check(&self, day: usize, answers: &[Answer]) -> Vec<(String, Verdict)> {
+        let mut output = vec![];
+
+        for Answer {
+            part, value: found, ..
+        } in answers
+        {
+            let verdict = match self.entries.get(&(day, part.clone())) {
+                Some(expected) if expected == found => Verdict::Pass,
+                Some(expected) => Verdict::Fail {
+                    expected: expected.clone(),
+                    found: Some(found.clone()),
+                },
+                None => Verdict::Missing {
+                    found: found.clone(),
+                },
+            };
+
+            output.push((part.clone(), verdict));
+        }
+
+        for ((d, part), expected) in &self.entries {
+            if *d == day && !any(answers, |a| &a.part == part) {
+                let verdict = Verdict::Fail {
+                    expected: expected.clone(),
+                    found: None,
+                };
+
+                output.push((part.clone(), verdict));
+            }
+        }
+
+        output
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::hash_map::{Entry, HashMap};
+use std::hash::Hash;
+
+/// A cycle in the sequence of states `x0, f(x0), f(f(x0)), ...`: the state at step
+/// `start + length` is the first to equal an earlier one, namely the state at step `start`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Cycle<T> {
+    pub start: usize,
+    pub length: usize,
+    /// The state at step zero.
+    pub initial: T,
+    /// The state at step `start`, which is the first state that repeats.
+    pub repeated: T,
+}
+
+impl<T: Clone> Cycle<T> {
+    /// Returns the earliest step at which the state is the same as at step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        iff!(
+            n < self.start,
+            n,
+            self.start + (n - self.start) % self.length
+        )
+    }
+
+    /// Returns the state at step `n`, taking fewer than `start + length` steps.
+    pub fn state_at<F>(&self, n: usize, mut step: F) -> T
+    where
+        F: FnMut(&T) -> T,
+    {
+        let (mut state, steps) = if n < self.start {
+            (self.initial.clone(), n)
+        } else {
+            (self.repeated.clone(), (n - self.start) % self.length)
+        };
+
+        for _ in 0..steps {
+            state = step(&state);
+        }
+
+        state
+    }
+}
+
+/// Finds the cycle by remembering every state, so that every state is computed only once.
+pub fn find_cycle<T, F>(initial: T, mut step: F) -> Cycle<T>
+where
+    T: Hash + Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    let mut seen = HashMap::new();
+    let mut state = initial.clone();
+
+    for curr in 0.. {
+        let next = match seen.entry(state) {
+            Entry::Occupied(entry) => {
+                return Cycle {
+                    start: *entry.get(),
+                    length: curr - entry.get(),
+                    initial,
+                    repeated: entry.key().clone(),
+                };
+            }
+            Entry::Vacant(entry) => step(entry.insert_entry(curr).key()),
+        };
+
+        state = next;
+    }
+
+    unreachable!()
+}
+
+/// Finds the cycle using Brent's algorithm, which keeps only two states in memory at the cost
+/// of computing every state up to three times.
+pub fn find_cycle_brent<T, F>(initial: T, mut step: F) -> Cycle<T>
+where
+    T: Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    // find the length by moving the hare ahead of the tortoise, which teleports to the hare
+    // every power of two steps
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // find the start by moving both one step at a time, with the hare `length` steps ahead
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle {
+        start,
+        length,
+        initial,
+        repeated: tortoise,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks both finders against the expected cycle, and `reduce` and `state_at` against
+    /// stepping one state at a time.
+    fn check(initial: u32, step: fn(&u32) -> u32, start: usize, length: usize) {
+        let cycle = find_cycle(initial, step);
+        assert_eq!((cycle.start, cycle.length), (start, length));
+        assert_eq!(cycle, find_cycle_brent(initial, step));
+
+        let mut state = initial;
+        for n in 0..100 {
+            assert_eq!(cycle.state_at(n, step), state, "step {}", n);
+
+            let reduced = cycle.reduce(n);
+            assert!(reduced < start + length);
+            assert_eq!(cycle.state_at(reduced, step), state, "step {}", n);
+
+            state = step(&state);
+        }
+    }
+
+    #[test]
+    fn cycle_with_tail() {
+        // 0, 1, 2, 3, then 4 to 9 over and over
+        check(0, |&x| iff!(x < 9, x + 1, 4), 4, 6);
+    }
+
+    #[test]
+    fn cycle_without_tail() {
+        check(0, |&x| (x + 1) % 5, 0, 5);
+    }
+
+    #[test]
+    fn cycle_of_length_one() {
+        check(0, |&x| u32::min(x + 1, 3), 3, 1);
+        check(7, |&x| x, 0, 1);
+    }
+
+    #[test]
+    fn state_before_and_after_start() {
+        let step = |&x: &u32| iff!(x < 9, x + 1, 4);
+        let cycle = find_cycle_brent(0, step);
+
+        assert_eq!(cycle.state_at(2, step), 2);
+        assert_eq!(cycle.state_at(4, step), 4);
+        assert_eq!(cycle.state_at(10, step), 4);
+        assert_eq!(cycle.state_at(1_000_000_001, step), 5);
+        assert_eq!(cycle.reduce(3), 3);
+        assert_eq!(cycle.reduce(11), 5);
+    }
+}
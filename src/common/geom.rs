@@ -0,0 +1,198 @@
+use super::*;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Implements the arithmetic operators for a point type with the given fields.
+macro_rules! impl_point_ops {
+    ($type:ident, $($field:ident),*) => {
+        impl Add for $type {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl Sub for $type {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        impl Neg for $type {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),* }
+            }
+        }
+
+        impl Mul<i64> for $type {
+            type Output = Self;
+
+            fn mul(self, factor: i64) -> Self {
+                Self { $($field: self.$field * factor),* }
+            }
+        }
+
+        impl AddAssign for $type {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $type {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl $type {
+            /// Returns the Manhattan distance to the origin.
+            pub fn manhattan(self) -> i64 {
+                0 $(+ self.$field.abs())*
+            }
+
+            /// Returns the Manhattan distance to `other`.
+            pub fn manhattan_to(self, other: Self) -> i64 {
+                (self - other).manhattan()
+            }
+        }
+    };
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point2 {
+    pub const ORIGIN: Point2 = Point2 { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+}
+
+impl_point_ops!(Point2, x, y);
+
+impl From<(i64, i64)> for Point2 {
+    fn from((x, y): (i64, i64)) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Converts to an offset as taken by `Grid::offset`.
+impl From<Point2> for (isize, isize) {
+    fn from(p: Point2) -> Self {
+        (p.x as isize, p.y as isize)
+    }
+}
+
+impl fmt::Display for Point2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point3 {
+    pub const ORIGIN: Point3 = Point3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl_point_ops!(Point3, x, y, z);
+
+impl From<(i64, i64, i64)> for Point3 {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl fmt::Display for Point3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// The direction in which y increases. Screen and grid coordinates, where the first row is at
+/// the top, point down.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum YAxis {
+    Up,
+    Down,
+}
+
+/// One of the four directions along the axes.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir4 {
+    /// All directions in clockwise order, starting at `Up`.
+    pub const ALL: [Dir4; 4] = [Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn reverse(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    /// Returns the step of length one in this direction.
+    pub fn delta(self, y_axis: YAxis) -> Point2 {
+        let up = iff!(y_axis == YAxis::Up, 1, -1);
+
+        match self {
+            Dir4::Up => Point2::new(0, up),
+            Dir4::Right => Point2::new(1, 0),
+            Dir4::Down => Point2::new(0, -up),
+            Dir4::Left => Point2::new(-1, 0),
+        }
+    }
+
+    /// Parses an arrow (`^>v<`), a compass point (`NESW`) or a letter (`URDL`).
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '^' | 'N' | 'U' => Some(Dir4::Up),
+            '>' | 'E' | 'R' => Some(Dir4::Right),
+            'v' | 'S' | 'D' => Some(Dir4::Down),
+            '<' | 'W' | 'L' => Some(Dir4::Left),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Dir4 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let mut chars = s.chars();
+
+        match (chars.next().and_then(Dir4::from_char), chars.next()) {
+            (Some(dir), None) => Ok(dir),
+            _ => Err(ParseError(format!("invalid direction {:?}", s))),
+        }
+    }
+}
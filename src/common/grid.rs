@@ -0,0 +1,357 @@
+use super::*;
+use std::ops::{Index, IndexMut};
+
+/// Offsets of the neighbours in the four cardinal directions: up, right, down and left.
+const DELTAS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets of the neighbours in all eight directions, clockwise starting at the top.
+const DELTAS8: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// A rectangular grid of tiles indexed by `(x, y)`, with `(0, 0)` at the top left and y
+/// increasing downwards.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    pub fn from_fn<F>(width: usize, height: usize, mut fun: F) -> Self
+    where
+        F: FnMut((usize, usize)) -> T,
+    {
+        let mut cells = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(fun((x, y)));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses every line of `input` as a row of tiles. Lines shorter than the longest line are
+    /// padded as if they ended in spaces, and empty lines at the end are ignored.
+    pub fn parse<F>(input: &str, mut tile: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Result<T>,
+    {
+        let lines = Span::new(input).lines().collect_vec();
+        let width = lines
+            .iter()
+            .map(|line| line.text.chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = lines.len();
+        let mut cells = Vec::with_capacity(width * height);
+
+        for line in lines {
+            let mut chars = line.chars();
+
+            for _ in 0..width {
+                // errors in the padding point just past the end of the line
+                let (c, span) = match chars.next() {
+                    Some(span) => (span.text.chars().next().unwrap(), span),
+                    None => (' ', line.end()),
+                };
+
+                cells.push(tile(c).map_err(|e| span.error(e))?);
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        iff!(self.contains(pos), Some(&self[pos]), None)
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        iff!(self.contains(pos), Some(&mut self[pos]), None)
+    }
+
+    /// Returns the position `(dx, dy)` away from `pos`, if it lies within the grid.
+    pub fn offset(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        iff!(self.contains(pos), Some(pos), None)
+    }
+
+    /// Returns the positions next to `pos` in the four cardinal directions that lie within the
+    /// grid.
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DELTAS4.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    /// Returns the positions around `pos`, including diagonals, that lie within the grid.
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DELTAS8.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    /// Returns all positions row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Returns all positions and their tiles row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        zip(self.positions(), &self.cells)
+    }
+
+    /// Returns the first position, row by row, of a tile matching `pred`.
+    pub fn find<F>(&self, mut pred: F) -> Option<(usize, usize)>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().find(|(_, v)| pred(v)).map(|(pos, _)| pos)
+    }
+
+    /// Returns the positions of all tiles matching `pred`.
+    pub fn find_all<'a, F>(&'a self, mut pred: F) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        F: FnMut(&T) -> bool + 'a,
+    {
+        self.iter()
+            .filter(move |(_, v)| pred(v))
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn count<F>(&self, mut pred: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.cells.iter().filter(|v| pred(v)).count()
+    }
+
+    pub fn map<U, F>(&self, fun: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(fun).collect(),
+        }
+    }
+
+    /// Changes the size of the grid. Tiles keep their position, tiles outside the new size are
+    /// dropped and new tiles are set to `value`.
+    pub fn resize(&mut self, width: usize, height: usize, value: T)
+    where
+        T: Clone,
+    {
+        let mut old = std::mem::take(&mut self.cells).into_iter();
+        let mut cells = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..usize::max(width, self.width) {
+                let tile = iff!(y < self.height && x < self.width, old.next(), None);
+
+                if x < width {
+                    cells.push(tile.unwrap_or_else(|| value.clone()));
+                }
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) out of bounds",
+            x,
+            y
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) out of bounds",
+            x,
+            y
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, row) in enumerate(self.cells.chunks(usize::max(self.width, 1))) {
+            if index > 0 {
+                writeln!(f)?;
+            }
+
+            for tile in row {
+                write!(f, "{}", tile)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::parse::InputError;
+
+    /// Parses walls and floors, rejecting anything else including the padding of short rows.
+    fn tile(c: char) -> Result<bool> {
+        match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => bail!("unknown tile {:?}", c),
+        }
+    }
+
+    fn position(input: &str) -> (usize, usize, String) {
+        let err = Grid::parse(input, tile).unwrap_err();
+        let err = err.downcast::<InputError>().unwrap();
+        (err.line, err.column, err.text)
+    }
+
+    #[test]
+    fn parse() {
+        let grid = Grid::parse("#..\n.#\n\n", Ok).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.to_string(), "#..\n.# ");
+
+        let grid = Grid::parse("#.#\n...", tile).unwrap();
+        assert_eq!(grid.count(|&wall| wall), 2);
+        assert_eq!(grid.find_all(|&wall| wall).collect_vec(), [(0, 0), (2, 0)]);
+
+        let grid = Grid::parse("", tile).unwrap();
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+    }
+
+    #[test]
+    fn ragged_rows() {
+        assert_eq!(position("###\n#x#\n###"), (2, 2, "x".to_string()));
+
+        // the missing tiles of a short row are reported just past its end
+        assert_eq!(position("###\n#.\n###"), (2, 3, String::new()));
+        assert_eq!(position("#\n###"), (1, 2, String::new()));
+        assert_eq!(position("##\n\n##"), (2, 1, String::new()));
+
+        let err = Grid::parse("##\n#", tile).unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 2: unknown tile ' ': \"\"");
+    }
+
+    #[test]
+    fn resize() {
+        let mut grid = Grid::from_fn(3, 2, |(x, y)| x + 10 * y);
+        assert_eq!(grid.to_string(), "012\n101112");
+
+        grid.resize(2, 3, 7);
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(
+            grid,
+            Grid::from_fn(2, 3, |(x, y)| iff!(y < 2, x + 10 * y, 7))
+        );
+
+        grid.resize(4, 1, 9);
+        assert_eq!(grid, Grid::from_fn(4, 1, |(x, _)| iff!(x < 2, x, 9)));
+
+        grid.resize(0, 0, 9);
+        assert_eq!(grid, Grid::new(0, 0, 0));
+
+        grid.resize(2, 2, 5);
+        assert_eq!(grid, Grid::new(2, 2, 5));
+        assert_eq!(grid.count(|&v| v == 5), 4);
+    }
+
+    #[test]
+    fn bounds() {
+        let mut grid = Grid::new(3, 2, '.');
+
+        assert!(grid.contains((2, 1)));
+        assert!(!grid.contains((3, 0)));
+        assert!(!grid.contains((0, 2)));
+        assert_eq!(grid.get((3, 1)), None);
+        assert_eq!(grid.get_mut((0, 2)), None);
+        *grid.get_mut((2, 1)).unwrap() = '#';
+        assert_eq!(grid.get((2, 1)), Some(&'#'));
+
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.offset((0, 0), (0, -1)), None);
+        assert_eq!(grid.offset((2, 1), (1, 0)), None);
+        assert_eq!(grid.offset((2, 1), (0, 1)), None);
+        assert_eq!(grid.offset((2, 1), (-2, -1)), Some((0, 0)));
+        assert_eq!(grid.offset((usize::MAX, 0), (1, 0)), None);
+
+        assert_eq!(grid.neighbors4((0, 0)).collect_vec(), [(1, 0), (0, 1)]);
+        assert_eq!(
+            grid.neighbors4((1, 1)).collect_vec(),
+            [(1, 0), (2, 1), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbors8((2, 0)).collect_vec(),
+            [(2, 1), (1, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbors8((1, 0)).count(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "(3, 0) out of bounds")]
+    fn index_out_of_bounds() {
+        let grid = Grid::new(3, 2, '.');
+        let _ = grid[(3, 0)];
+    }
+}
@@ -0,0 +1,282 @@
+use super::*;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Returns the greatest common divisor of `a` and `b`, which is zero only if both are zero.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// Returns the least common multiple of `a` and `b`, or `None` if it does not fit in a `u64`.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Extended Euclidean algorithm. Returns `(g, x, y)` such that `g = gcd(a, b)` and
+/// `a * x + b * y = g`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+
+    iff!(r0 < 0, (-r0, -x0, -y0), (r0, x0, y0))
+}
+
+/// Returns the `x` in `0..modulus` for which `a * x = 1 (mod modulus)`, or `None` if `a` and
+/// `modulus` are not coprime or the modulus is zero.
+pub fn mod_inverse(a: impl Into<i128>, modulus: u64) -> Option<u64> {
+    let (g, x, _) = extended_gcd(a.into(), modulus as i128);
+    iff!(
+        g == 1 && modulus > 0,
+        Some(x.rem_euclid(modulus as i128) as u64),
+        None
+    )
+}
+
+/// Chinese remainder theorem. Returns `(x, m)` such that `x = r (mod n)` for every `(r, n)` in
+/// `congruences`, where `m` is the least common multiple of the moduli and `x` lies in `0..m`.
+/// The moduli need not be coprime. Returns `None` if a modulus is zero, the congruences
+/// contradict each other or `m` does not fit in a `u64`.
+pub fn crt(congruences: &[(i64, u64)]) -> Option<(u64, u64)> {
+    let (mut x, mut m) = (0i128, 1i128);
+
+    for &(r, n) in congruences {
+        if n == 0 {
+            return None;
+        }
+
+        let n = n as i128;
+        let (g, p, _) = extended_gcd(m, n);
+        let diff = r as i128 - x;
+
+        if diff % g != 0 {
+            return None;
+        }
+
+        // x + m * k = r (mod n)  <=>  k = (diff / g) * p (mod n / g)
+        let step = n / g;
+        let k = ModInt::new(diff / g, step as u64) * ModInt::new(p, step as u64);
+        let lcm = m.checked_mul(step).filter(|&l| l <= u64::MAX as i128)?;
+
+        x = (x + m * k.value() as i128).rem_euclid(lcm);
+        m = lcm;
+    }
+
+    Some((x as u64, m as u64))
+}
+
+/// An integer modulo some modulus, which is fixed when the value is created. Arithmetic between
+/// two values requires them to have the same modulus and never overflows.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ModInt {
+    value: u64,
+    modulus: u64,
+}
+
+impl ModInt {
+    pub fn new(value: impl Into<i128>, modulus: u64) -> Self {
+        assert!(modulus > 0, "modulus must be positive");
+
+        Self {
+            value: value.into().rem_euclid(modulus as i128) as u64,
+            modulus,
+        }
+    }
+
+    /// Returns the representative in `0..modulus`.
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.modulus
+    }
+
+    /// Raises the value to the power `exp` by repeated squaring.
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = Self::new(1, self.modulus);
+
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result *= base;
+            }
+
+            base *= base;
+            exp /= 2;
+        }
+
+        result
+    }
+
+    /// Returns the multiplicative inverse, or `None` if the value is not coprime to the modulus.
+    pub fn inverse(self) -> Option<Self> {
+        let inv = mod_inverse(self.value, self.modulus)?;
+        Some(Self::new(inv, self.modulus))
+    }
+
+    fn check(self, other: Self) {
+        assert_eq!(self.modulus, other.modulus, "mismatched moduli");
+    }
+}
+
+impl Add for ModInt {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.check(other);
+        Self::new(self.value as i128 + other.value as i128, self.modulus)
+    }
+}
+
+impl Sub for ModInt {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.check(other);
+        Self::new(self.value as i128 - other.value as i128, self.modulus)
+    }
+}
+
+impl Mul for ModInt {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.check(other);
+        let product = self.value as u128 * other.value as u128;
+
+        Self {
+            value: (product % self.modulus as u128) as u64,
+            modulus: self.modulus,
+        }
+    }
+}
+
+impl Neg for ModInt {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-(self.value as i128), self.modulus)
+    }
+}
+
+impl AddAssign for ModInt {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for ModInt {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for ModInt {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl fmt::Display for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(lcm(1 << 32, 1 << 32), Some(1 << 32));
+        assert_eq!(lcm(u64::MAX, 2), None);
+        assert_eq!(lcm((1 << 32) + 1, 1 << 32), None);
+    }
+
+    #[test]
+    fn extended_gcd_coefficients() {
+        for &(a, b) in &[
+            (240, 46),
+            (46, 240),
+            (-240, 46),
+            (240, -46),
+            (7, 0),
+            (0, 7),
+            (17, 17),
+        ] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(
+                g,
+                gcd(a.unsigned_abs() as u64, b.unsigned_abs() as u64) as i128
+            );
+            assert_eq!(a * x + b * y, g, "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn inverses() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(1, 0), None);
+
+        let modulus = 18446744073709551557; // the largest prime below 2^64
+        for &a in &[2, (1 << 63) + 5, modulus - 1] {
+            let a = ModInt::new(a, modulus);
+            assert_eq!((a * a.inverse().unwrap()).value(), 1);
+        }
+
+        assert_eq!(ModInt::new(4, 10).inverse(), None);
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(ModInt::new(3, 7).pow(0).value(), 1);
+        assert_eq!(ModInt::new(3, 7).pow(6).value(), 1);
+        assert_eq!(ModInt::new(2, 1_000_000_007).pow(30).value(), 73_741_817);
+        assert_eq!(ModInt::new(5, 1).pow(3).value(), 0);
+
+        // Fermat's little theorem near the top of the u64 range
+        let modulus = 18446744073709551557;
+        let a = ModInt::new(u64::MAX, modulus);
+        assert_eq!(a.pow(modulus - 1).value(), 1);
+        assert_eq!(a.pow(modulus - 2), a.inverse().unwrap());
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 4), (0, 3)]), Some((3, 12)));
+
+        // moduli that are not coprime
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(3, 6), (3, 6)]), Some((3, 6)));
+
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(crt(&[(0, 1 << 40), (0, (1 << 40) - 1)]), None);
+    }
+}
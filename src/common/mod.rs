@@ -0,0 +1,468 @@
+pub use anyhow::{Context, Error};
+pub use itertools::{all, any, enumerate, zip, Itertools as _};
+use std::cell::RefCell;
+use std::default::Default;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::iter::Sum;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+pub use thiserror::Error;
+
+macro_rules! iff {
+    ($cond:expr, $a:expr, $b:expr) => {
+        if $cond {
+            $a
+        } else {
+            $b
+        }
+    };
+}
+
+macro_rules! format_err {
+    ($($msg:tt)*) => {
+        $crate::common::StringError(format!($($msg)*))
+    };
+}
+
+macro_rules! bail {
+    ($obj:expr) => {
+        bail!("{}", $obj)
+    };
+    ($($msg:tt)*) => {
+        return std::result::Result::Err(format_err!($($msg)*).into())
+    }
+}
+
+macro_rules! out {
+    ($($msg:tt)*) => {
+        $crate::common::write_output(format_args!($($msg)*))
+    };
+}
+
+macro_rules! outln {
+    () => {
+        out!("\n")
+    };
+    ($($msg:tt)*) => {
+        out!("{}\n", format_args!($($msg)*))
+    };
+}
+
+macro_rules! log {
+    ($level:expr, $($msg:tt)*) => {
+        if $crate::common::log_enabled(module_path!(), $level) {
+            $crate::common::write_log(module_path!(), $level, format_args!($($msg)*));
+        }
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! error {
+    ($($msg:tt)*) => { log!($crate::common::Level::Error, $($msg)*) };
+}
+
+#[allow(unused_macros)]
+macro_rules! warn {
+    ($($msg:tt)*) => { log!($crate::common::Level::Warn, $($msg)*) };
+}
+
+macro_rules! info {
+    ($($msg:tt)*) => { log!($crate::common::Level::Info, $($msg)*) };
+}
+
+macro_rules! debug {
+    ($($msg:tt)*) => { log!($crate::common::Level::Debug, $($msg)*) };
+}
+
+macro_rules! trace {
+    ($($msg:tt)*) => { log!($crate::common::Level::Trace, $($msg)*) };
+}
+
+macro_rules! answer {
+    ($part:ident, $($msg:tt)*) => {
+        $crate::common::report_answer(stringify!($part), format!($($msg)*))
+    };
+}
+
+pub mod cycles;
+pub mod geom;
+mod grid;
+pub mod math;
+pub mod parse;
+pub mod report;
+pub mod search;
+
+pub use grid::Grid;
+pub use parse::Span;
+
+#[derive(Error, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[error("{0}")]
+pub struct StringError(pub String);
+
+/// Error for puzzle input that does not have the expected format.
+#[derive(Error, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[error("{0}")]
+pub struct ParseError(pub String);
+
+/// Error for a puzzle input file that does not exist.
+#[derive(Error, Debug)]
+#[error("input file {path} not found")]
+pub struct MissingInput {
+    pub path: String,
+    #[source]
+    pub source: io::Error,
+}
+
+#[inline(always)]
+pub fn default<T: Default>() -> T {
+    T::default()
+}
+
+#[inline(always)]
+pub fn sum<I>(iter: I) -> I::Item
+where
+    I: IntoIterator,
+    I::Item: Sum,
+{
+    iter.into_iter().sum()
+}
+
+#[inline(always)]
+pub fn map<I, F, B>(fun: F, iter: I) -> impl Iterator<Item = B>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> B,
+{
+    iter.into_iter().map(fun)
+}
+
+pub fn cycle<T: Clone>(val: T) -> impl Iterator<Item = T> {
+    Some(val).into_iter().cycle()
+}
+
+pub type Result<T = (), E = Error> = std::result::Result<T, E>;
+
+/// Environment variable that selects the input directory if none is set by `set_input_dir`.
+pub const INPUT_DIR_VAR: &str = "ADVENT2019_INPUT_DIR";
+
+static INPUT_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+pub fn set_input_dir<P: Into<PathBuf>>(dir: P) {
+    *INPUT_DIR.write().unwrap() = Some(dir.into());
+}
+
+/// Returns the input directory given by `set_input_dir` or by `ADVENT2019_INPUT_DIR`, if any.
+fn configured_input_dir() -> Option<PathBuf> {
+    if let Some(dir) = &*INPUT_DIR.read().unwrap() {
+        return Some(dir.clone());
+    }
+
+    env::var_os(INPUT_DIR_VAR).map(PathBuf::from)
+}
+
+/// Returns the directory containing the puzzle inputs. In order of preference this is the
+/// directory given by `set_input_dir`, the one named by `ADVENT2019_INPUT_DIR`, or the first
+/// existing one of `inputs` in the working directory, `inputs` next to the executable and
+/// `src/inputs` in the crate.
+pub fn input_dir() -> PathBuf {
+    if let Some(dir) = configured_input_dir() {
+        return dir;
+    }
+
+    let mut candidates = vec![PathBuf::from("inputs")];
+
+    if let Some(dir) = env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+    {
+        candidates.push(dir.join("inputs"));
+    }
+
+    candidates.push(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("inputs"),
+    );
+
+    candidates
+        .iter()
+        .find(|p| p.is_dir())
+        .unwrap_or(&candidates[0])
+        .clone()
+}
+
+pub fn input_path(filename: &str) -> PathBuf {
+    input_dir().join(filename)
+}
+
+/// Reads the input file with the given name from the input directory.
+pub fn read_input(filename: &str) -> Result<String> {
+    // embedded inputs are only bypassed if an input directory was explicitly configured
+    #[cfg(feature = "embed-inputs")]
+    {
+        if let (None, Some(content)) = (configured_input_dir(), crate::embedded::get(filename)) {
+            return Ok(content.to_string());
+        }
+    }
+
+    read_input_file(&input_path(filename).to_string_lossy())
+}
+
+/// Reads the input file at `path`, or stdin if `path` is `-`.
+pub fn read_input_file(path: &str) -> Result<String> {
+    let mut content = String::new();
+
+    if path == "-" {
+        io::stdin()
+            .read_to_string(&mut content)
+            .context("error while reading stdin")?;
+    } else {
+        let mut f = match File::open(path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let path = path.to_string();
+                return Err(MissingInput { path, source: e }.into());
+            }
+            Err(e) => return Err(Error::new(e).context(format!("failed to open {}", path))),
+        };
+        f.read_to_string(&mut content)
+            .with_context(|| format!("error while reading {}", path))?;
+    }
+
+    Ok(content)
+}
+
+/// The answer to one part of a puzzle and the wall time spent since the previous answer.
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub part: String,
+    pub value: String,
+    pub elapsed: Duration,
+}
+
+struct Capture {
+    answers: Vec<Answer>,
+    echo: bool,
+    last: Instant,
+}
+
+/// Output of a solution that was buffered instead of written to stdout and stderr.
+#[derive(Debug, Default, Clone)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    /// Writes the buffered output to stderr and stdout.
+    pub fn print(&self) {
+        eprint!("{}", self.stderr);
+        print!("{}", self.stdout);
+    }
+}
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Capture>> = const { RefCell::new(None) };
+    static BUFFER: RefCell<Option<Output>> = const { RefCell::new(None) };
+}
+
+/// Writes the output of a solution to stdout, or to the buffer if output is being buffered.
+/// Solutions should use `out!` and `outln!` instead of `print!` and `println!`.
+pub fn write_output(args: fmt::Arguments) {
+    let args = BUFFER.with(|b| match &mut *b.borrow_mut() {
+        Some(buffer) => {
+            fmt::Write::write_fmt(&mut buffer.stdout, args).unwrap();
+            None
+        }
+        None => Some(args),
+    });
+
+    if let Some(args) = args {
+        print!("{}", args);
+    }
+}
+
+/// Calls `fun` and returns its result together with the output it wrote using `out!` and the
+/// logging macros.
+pub fn buffer_output<F, R>(fun: F) -> (R, Output)
+where
+    F: FnOnce() -> R,
+{
+    let prev = BUFFER.with(|b| b.replace(Some(default())));
+    let result = fun();
+    let buffer = BUFFER.with(|b| b.replace(prev));
+    (result, buffer.unwrap_or_default())
+}
+
+/// Importance of a log message. By default only warnings and errors are shown.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    const ALL: [Level; 5] = [
+        Level::Error,
+        Level::Warn,
+        Level::Info,
+        Level::Debug,
+        Level::Trace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+
+    /// Returns the level that is `delta` steps more verbose, saturating at both ends.
+    pub fn adjust(self, delta: isize) -> Self {
+        let index = (self as isize + delta).clamp(0, Self::ALL.len() as isize - 1);
+        Self::ALL[index as usize]
+    }
+}
+
+impl std::str::FromStr for Level {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL.iter().find(|l| l.name() == s) {
+            Some(&level) => Ok(level),
+            None => bail!("invalid log level {:?}", s),
+        }
+    }
+}
+
+/// Environment variable with the log filter used if none is set by `set_log_filter`.
+pub const LOG_VAR: &str = "ADVENT2019_LOG";
+
+/// Default log level and per-module levels. A module matches if any component of its path
+/// equals the name given in the filter, e.g. `day16` matches `advent2019::day16`.
+struct LogFilter {
+    default: Level,
+    modules: Vec<(String, Level)>,
+}
+
+static LOG_FILTER: RwLock<LogFilter> = RwLock::new(LogFilter {
+    default: Level::Warn,
+    modules: Vec::new(),
+});
+
+/// Sets the log filter from a comma-separated list of `MODULE=LEVEL` entries and at most one
+/// bare `LEVEL` which sets the default level, e.g. `info,day18=trace`. The default level is
+/// then adjusted by `verbosity` steps, as given by `-v` and `-q`.
+pub fn set_log_filter(spec: &str, verbosity: isize) -> Result {
+    let mut default = Level::Warn;
+    let mut modules = vec![];
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        match entry.find('=') {
+            Some(index) => {
+                let level = entry[index + 1..].parse()?;
+                modules.push((entry[..index].to_string(), level));
+            }
+            None => default = entry.parse()?,
+        }
+    }
+
+    *LOG_FILTER.write().unwrap() = LogFilter {
+        default: default.adjust(verbosity),
+        modules,
+    };
+    Ok(())
+}
+
+/// Returns whether messages of the given level from the given module are shown.
+pub fn log_enabled(module: &str, level: Level) -> bool {
+    let filter = LOG_FILTER.read().unwrap();
+    let max_level = filter
+        .modules
+        .iter()
+        .rev()
+        .find(|(name, _)| module.split("::").any(|m| m == name))
+        .map_or(filter.default, |&(_, level)| level);
+
+    level <= max_level
+}
+
+/// Writes a log message to stderr, or to the buffer if output is being buffered. Use the
+/// `error!`, `warn!`, `info!`, `debug!` and `trace!` macros instead.
+pub fn write_log(module: &str, level: Level, args: fmt::Arguments) {
+    let name = module.rsplit("::").next().unwrap_or(module);
+    let line = format!("[{} {}] {}\n", name, level.name(), args);
+
+    let line = BUFFER.with(|b| match &mut *b.borrow_mut() {
+        Some(buffer) => {
+            buffer.stderr += &line;
+            None
+        }
+        None => Some(line),
+    });
+
+    if let Some(line) = line {
+        eprint!("{}", line);
+    }
+}
+
+fn print_answer(part: &str, value: &str) {
+    if value.contains('\n') {
+        outln!("answer {}:\n{}", part, value);
+    } else {
+        outln!("answer {}: {}", part, value);
+    }
+}
+
+/// Prints the answer to one part of a puzzle, and records it if answers are being captured.
+pub fn report_answer(part: &str, value: String) {
+    let now = Instant::now();
+    let echo = CAPTURED.with(|c| match &mut *c.borrow_mut() {
+        Some(capture) => {
+            if capture.echo {
+                print_answer(part, &value);
+            }
+
+            capture.answers.push(Answer {
+                part: part.to_string(),
+                value,
+                elapsed: now - capture.last,
+            });
+            capture.last = Instant::now();
+            None
+        }
+        None => Some(value),
+    });
+
+    if let Some(value) = echo {
+        print_answer(part, &value);
+    }
+}
+
+/// Calls `fun` and returns its result together with the answers it reported. The answers are
+/// still printed if `echo` is true.
+pub fn capture_answers<F, R>(echo: bool, fun: F) -> (R, Vec<Answer>)
+where
+    F: FnOnce() -> R,
+{
+    let capture = Capture {
+        answers: vec![],
+        echo,
+        last: Instant::now(),
+    };
+
+    let prev = CAPTURED.with(|c| c.replace(Some(capture)));
+    let result = fun();
+    let capture = CAPTURED.with(|c| c.replace(prev));
+    (result, capture.map(|c| c.answers).unwrap_or_default())
+}
@@ -0,0 +1,402 @@
+use super::*;
+use regex::Regex;
+use std::convert::TryInto;
+use std::str::FromStr;
+
+/// An error in the puzzle input, pointing at the offending text.
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub struct InputError {
+    /// The name of the input file, which is filled in by the runner.
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}, ", file)?;
+        }
+
+        write!(
+            f,
+            "line {}, column {}: {}: {:?}",
+            self.line, self.column, self.message, self.text
+        )
+    }
+}
+
+/// A piece of the puzzle input together with the line and column (both starting at 1) at which
+/// it starts. Errors raised while parsing a span point at its position.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Span<'a> {
+    pub text: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<'a> Span<'a> {
+    /// Returns the whole input as a span.
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Returns an error for this span with the given message.
+    pub fn error(self, message: impl fmt::Display) -> Error {
+        InputError {
+            file: None,
+            line: self.line,
+            column: self.column,
+            text: self.text.to_string(),
+            message: message.to_string(),
+        }
+        .into()
+    }
+
+    /// Returns the span of `self.text[start..end]`.
+    fn slice(self, start: usize, end: usize) -> Self {
+        Cursor::new(self).slice(start, end)
+    }
+
+    /// Returns the span of a subslice of `self.text`.
+    fn subspan(self, part: &'a str) -> Self {
+        Cursor::new(self).subspan(part)
+    }
+
+    /// Returns the empty span just after the end of this span.
+    pub fn end(self) -> Self {
+        self.slice(self.text.len(), self.text.len())
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn trim(self) -> Self {
+        self.subspan(self.text.trim())
+    }
+
+    /// Returns the lines of the span, without their line endings. Empty lines at the end are
+    /// ignored.
+    pub fn lines(self) -> impl Iterator<Item = Span<'a>> {
+        let text = self.text.trim_end_matches(['\r', '\n']);
+        let mut cursor = Cursor::new(self);
+        text.lines().map(move |line| cursor.subspan(line))
+    }
+
+    /// Returns every character as a separate span.
+    pub fn chars(self) -> impl Iterator<Item = Span<'a>> {
+        let mut cursor = Cursor::new(self);
+        self.text
+            .char_indices()
+            .map(move |(index, c)| cursor.slice(index, index + c.len_utf8()))
+    }
+
+    pub fn split<'b>(self, separator: &'b str) -> impl Iterator<Item = Span<'a>> + 'b
+    where
+        'a: 'b,
+    {
+        let mut cursor = Cursor::new(self);
+        self.text
+            .split(separator)
+            .map(move |part| cursor.subspan(part))
+    }
+
+    pub fn split_whitespace(self) -> impl Iterator<Item = Span<'a>> {
+        let mut cursor = Cursor::new(self);
+        self.text
+            .split_whitespace()
+            .map(move |part| cursor.subspan(part))
+    }
+
+    /// Splits the span at the first occurrence of `separator`.
+    pub fn split_once(self, separator: &str) -> Result<(Span<'a>, Span<'a>)> {
+        match self.text.find(separator) {
+            Some(index) => Ok((
+                self.slice(0, index),
+                self.slice(index + separator.len(), self.text.len()),
+            )),
+            None => Err(self.error(format!("expected {:?}", separator))),
+        }
+    }
+
+    /// Splits the span after the first `count` characters.
+    pub fn split_chars(self, count: usize) -> Result<(Span<'a>, Span<'a>)> {
+        match self.text.char_indices().nth(count) {
+            Some((index, _)) => Ok((self.slice(0, index), self.slice(index, self.text.len()))),
+            None if self.text.chars().count() == count => {
+                Ok((self, self.slice(self.text.len(), self.text.len())))
+            }
+            None => Err(self.error(format!("expected at least {} characters", count))),
+        }
+    }
+
+    pub fn strip_prefix(self, prefix: &str) -> Option<Span<'a>> {
+        iff!(
+            self.text.starts_with(prefix),
+            Some(self.slice(prefix.len(), self.text.len())),
+            None
+        )
+    }
+
+    /// Splits the span at every `separator` into exactly `N` fields.
+    pub fn fields<const N: usize>(self, separator: &str) -> Result<[Span<'a>; N]> {
+        exactly(
+            self,
+            self.split(separator),
+            &format!("separated by {:?}", separator),
+        )
+    }
+
+    /// Splits the span at whitespace into exactly `N` words.
+    pub fn words<const N: usize>(self) -> Result<[Span<'a>; N]> {
+        exactly(self, self.split_whitespace(), "separated by whitespace")
+    }
+
+    /// Parses the span, ignoring surrounding whitespace.
+    pub fn parse<T>(self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let span = self.trim();
+        span.text.parse().map_err(|e| span.error(e))
+    }
+
+    /// Parses a list of values separated by `separator`, ignoring surrounding whitespace.
+    pub fn parse_list<T>(self, separator: &str) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.trim().split(separator).map(Span::parse).collect()
+    }
+
+    /// Matches the span against `regex`, which must match.
+    pub fn captures(self, regex: &Regex) -> Result<Captures<'a>> {
+        match regex.captures(self.text) {
+            Some(captures) => Ok(Captures {
+                span: self,
+                captures,
+            }),
+            None => Err(self.error(format!("expected text matching {:?}", regex.as_str()))),
+        }
+    }
+}
+
+/// The position of an offset into a span. Moving the cursor only scans the text between the old
+/// and the new offset, so that splitting a span into parts takes linear time.
+struct Cursor<'a> {
+    span: Span<'a>,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(span: Span<'a>) -> Self {
+        Self {
+            span,
+            offset: 0,
+            line: span.line,
+            column: span.column,
+        }
+    }
+
+    /// Returns the span of `text[start..end]`, where `start` is not before the previous start.
+    fn slice(&mut self, start: usize, end: usize) -> Span<'a> {
+        for b in self.span.text[self.offset..start].bytes() {
+            if b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if b & 0xc0 != 0x80 {
+                // every character has exactly one byte that does not continue another one
+                self.column += 1;
+            }
+        }
+
+        self.offset = start;
+        Span {
+            text: &self.span.text[start..end],
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn subspan(&mut self, part: &'a str) -> Span<'a> {
+        let start = part.as_ptr() as usize - self.span.text.as_ptr() as usize;
+        self.slice(start, start + part.len())
+    }
+}
+
+impl fmt::Display for Span<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
+fn exactly<'a, I, const N: usize>(span: Span<'a>, parts: I, how: &str) -> Result<[Span<'a>; N]>
+where
+    I: Iterator<Item = Span<'a>>,
+{
+    let parts = parts.collect_vec();
+    let count = parts.len();
+
+    parts
+        .try_into()
+        .map_err(|_| span.error(format!("expected {} fields {}, found {}", N, how, count)))
+}
+
+/// The groups captured by matching a regular expression against a span.
+pub struct Captures<'a> {
+    span: Span<'a>,
+    captures: regex::Captures<'a>,
+}
+
+impl<'a> Captures<'a> {
+    /// Returns the span of the given group, which must have been matched.
+    pub fn get(&self, group: usize) -> Result<Span<'a>> {
+        match self.captures.get(group) {
+            Some(m) => Ok(self.span.slice(m.start(), m.end())),
+            None => Err(self.span.error(format!("group {} did not match", group))),
+        }
+    }
+
+    /// Parses the text of the given group.
+    pub fn parse<T>(&self, group: usize) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.get(group)?.parse()
+    }
+
+    /// Parses every group into a value of the same type.
+    pub fn parse_all<T>(&self) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        map(|group| self.parse(group), 1..self.captures.len()).collect()
+    }
+}
+
+/// Returns the lines of `input` as spans.
+pub fn lines(input: &str) -> impl Iterator<Item = Span<'_>> {
+    Span::new(input).lines()
+}
+
+/// Parses a list of values separated by `separator`, such as a comma-separated list of numbers.
+pub fn parse_list<T>(input: &str, separator: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    Span::new(input).parse_list(separator)
+}
+
+/// Fills in the name of the input file in `err`, if it is an error in the puzzle input.
+pub fn set_input_file(err: &mut Error, file: &str) {
+    if let Some(e) = err.downcast_mut::<InputError>() {
+        e.file.get_or_insert_with(|| file.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(span: Span<'_>) -> (&str, usize, usize) {
+        (span.text, span.line, span.column)
+    }
+
+    #[test]
+    fn line_positions() {
+        let input = "abc\n\n  de\r\nf\n\n";
+        let spans = lines(input).map(position).collect_vec();
+        assert_eq!(
+            spans,
+            [("abc", 1, 1), ("", 2, 1), ("  de", 3, 1), ("f", 4, 1)]
+        );
+
+        let words = lines(input)
+            .flat_map(Span::split_whitespace)
+            .map(position)
+            .collect_vec();
+        assert_eq!(words, [("abc", 1, 1), ("de", 3, 3), ("f", 4, 1)]);
+    }
+
+    #[test]
+    fn split_positions() {
+        let span = Span::new("1,22,\n333, 4");
+        let parts = span.split(",").map(position).collect_vec();
+        assert_eq!(
+            parts,
+            [("1", 1, 1), ("22", 1, 3), ("\n333", 1, 6), (" 4", 2, 5)]
+        );
+
+        let trimmed = span.split(",").map(|s| position(s.trim())).collect_vec();
+        assert_eq!(trimmed[2], ("333", 2, 1));
+        assert_eq!(trimmed[3], ("4", 2, 6));
+
+        let (left, right) = Span::new("x)\ny)z").split_once(")").unwrap();
+        assert_eq!(
+            (position(left), position(right)),
+            (("x", 1, 1), ("\ny)z", 1, 3))
+        );
+    }
+
+    #[test]
+    fn character_positions() {
+        // columns count characters, not bytes
+        let span = Span::new("aé\nüb");
+        let chars = span.chars().map(position).collect_vec();
+        assert_eq!(
+            chars,
+            [
+                ("a", 1, 1),
+                ("é", 1, 2),
+                ("\n", 1, 3),
+                ("ü", 2, 1),
+                ("b", 2, 2)
+            ]
+        );
+
+        let (head, tail) = Span::new("éé12").split_chars(2).unwrap();
+        assert_eq!(
+            (position(head), position(tail)),
+            (("éé", 1, 1), ("12", 1, 3))
+        );
+        assert_eq!(position(tail.end()), ("", 1, 5));
+    }
+
+    #[test]
+    fn nested_positions() {
+        let line = lines("skip\n  A)B, C)D").nth(1).unwrap();
+        let [_, second] = line.fields::<2>(",").unwrap();
+        let [left, right] = second.trim().fields::<2>(")").unwrap();
+        assert_eq!(position(left), ("C", 2, 8));
+        assert_eq!(position(right), ("D", 2, 10));
+
+        let regex = Regex::new(r"(\w)\)(\w)").unwrap();
+        let captures = second.captures(&regex).unwrap();
+        assert_eq!(position(captures.get(2).unwrap()), ("D", 2, 10));
+    }
+
+    #[test]
+    fn error_positions() {
+        let err = parse_list::<i64>("1,2,\n3,x", ",").unwrap_err();
+        let err = err.downcast::<InputError>().unwrap();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "x"));
+
+        let mut err = Span::new("a\nbc").lines().nth(1).unwrap().error("oops");
+        set_input_file(&mut err, "day01");
+        assert_eq!(err.to_string(), "day01, line 2, column 1: oops: \"bc\"");
+    }
+}
@@ -0,0 +1,308 @@
+use super::*;
+use std::str::FromStr;
+
+/// Format in which a report is written.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// Aligned columns for reading in a terminal.
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(ParseError(format!("unknown report format {:?}", s))),
+        }
+    }
+}
+
+/// A value in a report.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Value {
+    Number(i128),
+    Text(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn is_number(&self) -> bool {
+        matches!(self, Value::Number(_))
+    }
+
+    /// Formats the value for text and CSV reports, where the items of lists are separated by
+    /// spaces.
+    fn plain(&self) -> String {
+        match self {
+            Value::Number(n) => n.to_string(),
+            Value::Text(s) => s.clone(),
+            Value::List(items) => items.iter().map(Value::plain).join(" "),
+        }
+    }
+
+    fn csv(&self) -> String {
+        let s = self.plain();
+        iff!(
+            s.contains(['"', ',', '\n']),
+            format!("\"{}\"", s.replace('"', "\"\"")),
+            s
+        )
+    }
+
+    fn json(&self) -> String {
+        match self {
+            Value::Number(n) => n.to_string(),
+            Value::Text(s) => json_string(s),
+            Value::List(items) => format!("[{}]", items.iter().map(Value::json).join(", ")),
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+macro_rules! impl_number {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for Value {
+            fn from(n: $ty) -> Self {
+                Value::Number(n as i128)
+            }
+        })*
+    };
+}
+
+impl_number!(i32, i64, u32, u64, usize);
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Value::List(map(Into::into, items).collect())
+    }
+}
+
+/// A table with a row for every item and optional totals of some of the columns, which can be
+/// written in any of the report formats.
+pub struct Report {
+    /// What the rows are, which names the list of rows in JSON.
+    name: &'static str,
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+    totals: Vec<(usize, Value)>,
+}
+
+impl Report {
+    pub fn new(name: &'static str, columns: &[&'static str]) -> Self {
+        Self {
+            name,
+            columns: columns.to_vec(),
+            rows: vec![],
+            totals: vec![],
+        }
+    }
+
+    /// Adds a row with a value for every column.
+    pub fn row(&mut self, values: Vec<Value>) {
+        assert_eq!(values.len(), self.columns.len(), "wrong number of values");
+        self.rows.push(values);
+    }
+
+    /// Sets the total of the given column, which cannot be the first column because that
+    /// labels the totals in text and CSV reports.
+    pub fn total(&mut self, column: &str, value: impl Into<Value>) {
+        let index = self
+            .columns
+            .iter()
+            .position(|&c| c == column)
+            .expect("unknown column");
+        assert!(index > 0, "the first column cannot have a total");
+        self.totals.push((index, value.into()));
+    }
+
+    /// Returns the totals as a row, with "total" in the first column.
+    fn totals_row(&self) -> Option<Vec<String>> {
+        if self.totals.is_empty() {
+            return None;
+        }
+
+        let mut row = vec![String::new(); self.columns.len()];
+        row[0] = "total".to_string();
+        for (index, value) in &self.totals {
+            row[*index] = value.plain();
+        }
+
+        Some(row)
+    }
+
+    /// Writes the report to the output.
+    pub fn write(&self, format: Format) {
+        for line in self.lines(format) {
+            outln!("{}", line);
+        }
+    }
+
+    /// Returns the lines of the report in the given format.
+    pub fn lines(&self, format: Format) -> Vec<String> {
+        match format {
+            Format::Text => self.text(),
+            Format::Json => self.json(),
+            Format::Csv => self.csv(),
+        }
+    }
+
+    fn text(&self) -> Vec<String> {
+        let n = self.columns.len();
+        let mut lines = vec![map(|c| c.to_string(), &self.columns).collect_vec()];
+        lines.extend(self.rows.iter().map(|row| map(Value::plain, row).collect()));
+        lines.extend(self.totals_row());
+
+        // columns of numbers are aligned to the right, including their headers
+        let widths = map(|i| lines.iter().map(|l| l[i].chars().count()).max(), 0..n)
+            .map(Option::unwrap)
+            .collect_vec();
+        let right = map(|i| self.rows.iter().all(|row| row[i].is_number()), 0..n).collect_vec();
+
+        map(
+            |line| {
+                let mut cells = map(
+                    |(i, cell)| {
+                        iff!(
+                            right[i],
+                            format!("{:>1$}", cell, widths[i]),
+                            format!("{:<1$}", cell, widths[i])
+                        )
+                    },
+                    enumerate(line),
+                );
+                cells.join("  ").trim_end().to_string()
+            },
+            lines,
+        )
+        .collect()
+    }
+
+    fn json(&self) -> Vec<String> {
+        let object = |pairs: Vec<(usize, &Value)>| {
+            let mut fields = map(
+                |(i, v)| format!("{}: {}", json_string(self.columns[i]), v.json()),
+                pairs,
+            );
+            format!("{{{}}}", fields.join(", "))
+        };
+
+        let mut lines = vec!["{".to_string(), format!("  {}: [", json_string(self.name))];
+
+        for (i, row) in enumerate(&self.rows) {
+            lines.push(format!(
+                "    {}{}",
+                object(enumerate(row).collect()),
+                iff!(i + 1 < self.rows.len(), ",", "")
+            ));
+        }
+
+        let totals = object(map(|(i, v)| (*i, v), &self.totals).collect());
+        lines.push("  ],".to_string());
+        lines.push(format!("  \"totals\": {}", totals));
+        lines.push("}".to_string());
+        lines
+    }
+
+    fn csv(&self) -> Vec<String> {
+        let mut lines = vec![self.columns.join(",")];
+        lines.extend(
+            self.rows
+                .iter()
+                .map(|row| row.iter().map(Value::csv).join(",")),
+        );
+
+        if let Some(row) = self.totals_row() {
+            lines.push(map(|s| Value::Text(s).csv(), row).join(","));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Report {
+        let mut report = Report::new("items", &["name", "id", "sizes"]);
+        report.row(vec!["a, \"b\"".into(), 1.into(), vec![10, 200].into()]);
+        report.row(vec!["c".into(), 20.into(), Vec::<u64>::new().into()]);
+        report.total("id", 21);
+        report
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            example().lines(Format::Text),
+            [
+                "name    id  sizes",
+                "a, \"b\"   1  10 200",
+                "c       20",
+                "total   21",
+            ]
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            example().lines(Format::Json),
+            [
+                "{",
+                "  \"items\": [",
+                "    {\"name\": \"a, \\\"b\\\"\", \"id\": 1, \"sizes\": [10, 200]},",
+                "    {\"name\": \"c\", \"id\": 20, \"sizes\": []}",
+                "  ],",
+                "  \"totals\": {\"id\": 21}",
+                "}",
+            ]
+        );
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            example().lines(Format::Csv),
+            [
+                "name,id,sizes",
+                "\"a, \"\"b\"\"\",1,10 200",
+                "c,20,",
+                "total,21,",
+            ]
+        );
+    }
+}
@@ -0,0 +1,402 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry as MapEntry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The outcome of a search from a start node: the distance to every node that was reached and,
+/// if paths were tracked, the node preceding it on a shortest path.
+#[derive(Clone, Debug)]
+pub struct Search<N, C> {
+    pub start: N,
+    pub dist: HashMap<N, C>,
+    pub prev: Option<HashMap<N, N>>,
+    /// The first node matching the goal predicate, if the search stopped at one.
+    pub goal: Option<N>,
+}
+
+impl<N, C> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy,
+{
+    fn new(start: N, zero: C, paths: bool) -> Self {
+        let mut dist = HashMap::new();
+        dist.insert(start.clone(), zero);
+
+        Self {
+            start,
+            dist,
+            prev: iff!(paths, Some(HashMap::new()), None),
+            goal: None,
+        }
+    }
+
+    fn set_prev(&mut self, node: &N, prev: &N) {
+        if let Some(map) = &mut self.prev {
+            map.insert(node.clone(), prev.clone());
+        }
+    }
+
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.dist.get(node).copied()
+    }
+
+    /// Returns the nodes on a shortest path from the start to `node`, including both. Returns
+    /// `None` if the node was not reached or paths were not tracked.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        let prevs = self.prev.as_ref()?;
+        if !self.dist.contains_key(node) {
+            return None;
+        }
+
+        let mut path = vec![node.clone()];
+        while let Some(prev) = prevs.get(path.last().unwrap()) {
+            path.push(prev.clone());
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn goal_distance(&self) -> Option<C> {
+        self.distance(self.goal.as_ref()?)
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+}
+
+/// Breadth-first search from `start`, where every edge has length one. `successors` returns the
+/// neighbours of a node, and the search stops at the first node for which `is_goal` is true.
+/// Only distances are recorded; use `bfs_with_paths` to also find the paths.
+pub fn bfs<N, I, F, G>(start: N, successors: F, is_goal: G) -> Search<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    breadth_first(start, successors, is_goal, false)
+}
+
+/// Like `bfs`, but also records the shortest paths.
+pub fn bfs_with_paths<N, I, F, G>(start: N, successors: F, is_goal: G) -> Search<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    breadth_first(start, successors, is_goal, true)
+}
+
+fn breadth_first<N, I, F, G>(
+    start: N,
+    mut successors: F,
+    mut is_goal: G,
+    paths: bool,
+) -> Search<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    let mut search = Search::new(start.clone(), 0, paths);
+    let mut queue = VecDeque::new();
+    queue.push_back((start, 0));
+
+    while let Some((node, d)) = queue.pop_front() {
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        for next in successors(&node) {
+            if let MapEntry::Vacant(entry) = search.dist.entry(next.clone()) {
+                entry.insert(d + 1);
+                search.set_prev(&next, &node);
+                queue.push_back((next, d + 1));
+            }
+        }
+    }
+
+    search
+}
+
+/// An entry of the priority queue, ordered such that the smallest priority is popped first.
+struct Entry<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Entry<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for Entry<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Entry<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Entry<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Dijkstra's algorithm from `start`. `successors` returns the neighbours of a node together
+/// with the length of the edge to them, and the search stops at the first node for which
+/// `is_goal` is true. Only distances are recorded; use `dijkstra_with_paths` to also find the
+/// paths.
+pub fn dijkstra<N, C, I, F, G>(start: N, successors: F, is_goal: G) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Ord + Copy + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+    F: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    best_first(start, successors, |_| C::default(), is_goal, false)
+}
+
+/// Like `dijkstra`, but also records the shortest paths.
+pub fn dijkstra_with_paths<N, C, I, F, G>(start: N, successors: F, is_goal: G) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Ord + Copy + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+    F: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    best_first(start, successors, |_| C::default(), is_goal, true)
+}
+
+/// A* search from `start`. Like `dijkstra`, but nodes are visited in the order of their distance
+/// plus `heuristic`, which must never overestimate the distance to the nearest goal.
+pub fn astar<N, C, I, F, H, G>(start: N, successors: F, heuristic: H, is_goal: G) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Ord + Copy + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+    F: FnMut(&N) -> I,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    best_first(start, successors, heuristic, is_goal, false)
+}
+
+/// Like `astar`, but also records the shortest paths.
+pub fn astar_with_paths<N, C, I, F, H, G>(
+    start: N,
+    successors: F,
+    heuristic: H,
+    is_goal: G,
+) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Ord + Copy + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+    F: FnMut(&N) -> I,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    best_first(start, successors, heuristic, is_goal, true)
+}
+
+fn best_first<N, C, I, F, H, G>(
+    start: N,
+    mut successors: F,
+    mut heuristic: H,
+    mut is_goal: G,
+    paths: bool,
+) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Ord + Copy + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+    F: FnMut(&N) -> I,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    let zero = C::default();
+    let mut search = Search::new(start.clone(), zero, paths);
+    let mut queue = BinaryHeap::new();
+
+    queue.push(Entry {
+        priority: heuristic(&start),
+        cost: zero,
+        node: start,
+    });
+
+    while let Some(Entry { cost, node, .. }) = queue.pop() {
+        // skip entries for nodes that were reached by a shorter path after being queued
+        if search.dist.get(&node).is_some_and(|&d| d < cost) {
+            continue;
+        }
+
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        for (next, len) in successors(&node) {
+            let next_cost = cost + len;
+
+            match search.dist.entry(next.clone()) {
+                MapEntry::Occupied(entry) if *entry.get() <= next_cost => continue,
+                MapEntry::Occupied(mut entry) => *entry.get_mut() = next_cost,
+                MapEntry::Vacant(entry) => {
+                    entry.insert(next_cost);
+                }
+            }
+
+            search.set_prev(&next, &node);
+            queue.push(Entry {
+                priority: next_cost + heuristic(&next),
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A weighted directed graph in which node 5 cannot be reached from node 0.
+    const EDGES: &[(u32, u32, u32)] = &[
+        (0, 1, 10),
+        (0, 2, 1),
+        (2, 1, 2),
+        (1, 3, 1),
+        (2, 4, 7),
+        (3, 4, 1),
+        (5, 0, 1),
+    ];
+
+    fn successors(&node: &u32) -> Vec<(u32, u32)> {
+        EDGES
+            .iter()
+            .filter(|&&(from, _, _)| from == node)
+            .map(|&(_, to, len)| (to, len))
+            .collect()
+    }
+
+    fn neighbors(node: &u32) -> Vec<u32> {
+        successors(node).into_iter().map(|(to, _)| to).collect()
+    }
+
+    /// Returns the length of `path` if it is a path along the edges of the graph.
+    fn length(path: &[u32]) -> Option<u32> {
+        path.windows(2)
+            .map(|w| {
+                successors(&w[0])
+                    .iter()
+                    .find(|&&(to, _)| to == w[1])
+                    .map(|e| e.1)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn breadth_first_paths() {
+        let search = bfs_with_paths(0, neighbors, |&n| n == 4);
+        assert_eq!(search.goal, Some(4));
+        assert_eq!(search.goal_distance(), Some(2));
+        assert_eq!(search.goal_path(), Some(vec![0, 2, 4]));
+        assert_eq!(search.path_to(&0), Some(vec![0]));
+
+        // without tracking the distances are the same, but there are no paths
+        let search = bfs(0, neighbors, |&n| n == 4);
+        assert_eq!(search.goal_distance(), Some(2));
+        assert_eq!(search.goal_path(), None);
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let search = dijkstra_with_paths(0, successors, |_| false);
+        assert_eq!(search.goal, None);
+        assert_eq!(
+            (0..6).map(|n| search.distance(&n)).collect::<Vec<_>>(),
+            [Some(0), Some(3), Some(1), Some(4), Some(5), None,]
+        );
+
+        // the longer path to 1 was found first and replaced by the shorter one through 2
+        assert_eq!(search.path_to(&1), Some(vec![0, 2, 1]));
+        assert_eq!(search.path_to(&4), Some(vec![0, 2, 1, 3, 4]));
+        for n in 0..5 {
+            let path = search.path_to(&n).unwrap();
+            assert_eq!(length(&path), search.distance(&n));
+        }
+
+        let search = dijkstra(0, successors, |&n| n == 4);
+        assert_eq!(search.goal_distance(), Some(5));
+        assert_eq!(search.path_to(&4), None);
+    }
+
+    #[test]
+    fn unreachable() {
+        let search = bfs_with_paths(0, neighbors, |&n| n == 5);
+        assert_eq!(search.goal, None);
+        assert_eq!(search.goal_distance(), None);
+        assert_eq!(search.goal_path(), None);
+        assert_eq!(search.distance(&5), None);
+        assert_eq!(search.path_to(&5), None);
+        assert_eq!(search.dist.len(), 5);
+
+        let search = dijkstra_with_paths(0, successors, |&n| n == 5);
+        assert_eq!((search.goal_distance(), search.path_to(&5)), (None, None));
+
+        let search = astar_with_paths(0, successors, |_| 0, |&n| n == 5);
+        assert_eq!((search.goal_distance(), search.path_to(&5)), (None, None));
+    }
+
+    #[test]
+    fn ties() {
+        // a 4x4 grid in which every shortest path from corner to corner has the same length
+        let grid = |&(x, y): &(i32, i32)| {
+            [(x + 1, y), (x, y + 1), (x - 1, y), (x, y - 1)]
+                .iter()
+                .copied()
+                .filter(|&(x, y)| (0..4).contains(&x) && (0..4).contains(&y))
+                .map(|p| (p, 1))
+                .collect::<Vec<_>>()
+        };
+        let goal = |&p: &(i32, i32)| p == (3, 3);
+        let heuristic = |&(x, y): &(i32, i32)| (3 - x) + (3 - y);
+
+        let searches = [
+            dijkstra_with_paths((0, 0), grid, goal),
+            astar_with_paths((0, 0), grid, heuristic, goal),
+        ];
+
+        for search in &searches {
+            assert_eq!(search.goal_distance(), Some(6));
+
+            let path = search.goal_path().unwrap();
+            assert_eq!(path.len(), 7);
+            assert_eq!((path[0], path[6]), ((0, 0), (3, 3)));
+            for w in path.windows(2) {
+                let ((ax, ay), (bx, by)) = (w[0], w[1]);
+                assert_eq!((ax - bx).abs() + (ay - by).abs(), 1);
+            }
+        }
+
+        // with equal costs the first path found is kept, which for a breadth-first search is
+        // the one through the first successor
+        let search = bfs_with_paths(0, |&n: &u32| iff!(n == 0, vec![1, 2], vec![3]), |&n| n == 3);
+        assert_eq!(search.goal_path(), Some(vec![0, 1, 3]));
+    }
+}
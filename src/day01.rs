@@ -1,31 +1,115 @@
+use crate::common::parse::lines;
+use crate::common::report::{Format, Report};
 use crate::common::*;
+use crate::solution::Solution;
 
-/// Simply calculates the required fuel for the given mass.
-fn fuel_for_mass_simple(x: f64) -> f64 {
-    (x / 3.0).floor() - 2.0
+/// Simply calculates the required fuel for the given mass: a third of the mass, rounded down,
+/// minus two. Masses too small to need fuel need none.
+pub fn fuel_for_mass(mass: u64) -> u64 {
+    (mass / 3).saturating_sub(2)
 }
 
-/// Calculates the required fuel for the given mass, and fuel for this required fuel, and the
-/// fuel for this required fuel, ad infinitum.
-fn fuel_for_mass_complex(x: f64) -> f64 {
-    let f = fuel_for_mass_simple(x);
-    iff!(f > 0.0, f + fuel_for_mass_complex(f), 0.0)
+/// Returns the fuel for the given mass, the fuel for this fuel, the fuel for that fuel, and so
+/// on, until no more fuel is needed.
+pub fn fuel_chain(mass: u64) -> impl Iterator<Item = u64> {
+    std::iter::successors(Some(fuel_for_mass(mass)), |&f| Some(fuel_for_mass(f)))
+        .take_while(|&f| f > 0)
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let mut part_a = 0.0;
-    let mut part_b = 0.0;
+/// Returns the fuel for the given mass including the fuel for the fuel. Every link of the chain
+/// is at most a third of the previous one, so the total is less than half the mass and cannot
+/// overflow.
+pub fn total_fuel_for_mass(mass: u64) -> u64 {
+    sum(fuel_chain(mass))
+}
+
+fn parse_args(args: &[&str]) -> Result<Option<Format>> {
+    match args {
+        [] => Ok(None),
+        ["--report", format] => Ok(Some(format.parse()?)),
+        _ => bail!("usage: 1 [--report text|json|csv]"),
+    }
+}
+
+pub fn run(input: &str, args: &[&str]) -> Result {
+    let format = parse_args(args)?;
+    let mut report = Report::new("modules", &["line", "mass", "fuel", "chain", "total"]);
+    let mut part_a = 0u64;
+    let mut part_b = 0u64;
+
+    for line in lines(input) {
+        let mass = line.parse::<u64>()?;
+        let (fuel, total) = (fuel_for_mass(mass), total_fuel_for_mass(mass));
 
-    for (index, line) in enumerate(read_input("day01")?) {
-        let mass = line
-            .parse::<f64>()
-            .with_context(|| format!("failed to parse mass on line {}", index + 1))?;
-        part_a += fuel_for_mass_simple(mass);
-        part_b += fuel_for_mass_complex(mass);
+        part_a = part_a
+            .checked_add(fuel)
+            .context("total fuel overflows u64")?;
+        part_b = part_b
+            .checked_add(total)
+            .context("total fuel overflows u64")?;
+
+        if format.is_some() {
+            let chain = fuel_chain(mass).collect_vec();
+            report.row(vec![
+                line.line.into(),
+                mass.into(),
+                fuel.into(),
+                chain.into(),
+                total.into(),
+            ]);
+        }
+    }
+
+    if let Some(format) = format {
+        report.total("fuel", part_a);
+        report.total("total", part_b);
+        report.write(format);
     }
 
-    println!("answer A: {}", part_a);
-    println!("answer B: {}", part_b);
+    answer!(A, "{}", part_a);
+    answer!(B, "{}", part_b);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "The Tyranny of the Rocket Equation"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples() {
+        assert_eq!(fuel_for_mass(12), 2);
+        assert_eq!(fuel_for_mass(14), 2);
+        assert_eq!(fuel_for_mass(1969), 654);
+        assert_eq!(fuel_for_mass(100756), 33583);
+        assert_eq!(total_fuel_for_mass(14), 2);
+        assert_eq!(total_fuel_for_mass(1969), 966);
+        assert_eq!(total_fuel_for_mass(100756), 50346);
+    }
+
+    #[test]
+    fn extreme_masses() {
+        assert_eq!(fuel_for_mass(0), 0);
+        assert_eq!(fuel_for_mass(8), 0);
+        assert_eq!(fuel_for_mass(9), 1);
+        assert_eq!(fuel_for_mass(u64::MAX), u64::MAX / 3 - 2);
+        assert!(total_fuel_for_mass(u64::MAX) < u64::MAX / 2);
+        assert_eq!(fuel_chain(u64::MAX).last(), Some(1));
+    }
+}
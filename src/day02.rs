@@ -1,14 +1,15 @@
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let original = parse_program("day02")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let original = parse_program(input)?;
 
     let mut program = original.clone();
     program.set(1, 12)?;
     program.set(2, 2)?;
     program.run(&[])?;
-    println!("answer A: {}", program.get(0)?);
+    answer!(A, "{}", program.get(0)?);
 
     for noun in 0..100 {
         for verb in 0..100 {
@@ -21,10 +22,30 @@ pub(crate) fn run(_args: &[&str]) -> Result {
             }
 
             if program.get(0)? == 19_690_720 {
-                println!("answer B: {}", 100 * noun + verb);
+                answer!(B, "{}", 100 * noun + verb);
             }
         }
     }
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "1202 Program Alarm"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
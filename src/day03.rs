@@ -1,101 +1,553 @@
+use crate::common::geom::{Dir4, Point2, YAxis};
+use crate::common::parse::lines;
 use crate::common::*;
+use crate::image::{exporter, Rgb};
+use crate::render::Color;
+use crate::solution::Solution;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+/// A straight piece of wire from `start` to `end`, which lie on the same row or column.
 #[derive(Copy, Clone, Debug)]
-enum Line {
-    Horz(i64, i64, i64), // x, y, length along x axis
-    Vert(i64, i64, i64), // x, y, length along y axis
+struct Segment {
+    wire: usize,
+    start: Point2,
+    end: Point2,
+    /// The number of steps along the wire before the start of the segment.
+    delay: i64,
 }
 
-impl Line {
-    fn length(&self) -> i64 {
-        match *self {
-            Line::Horz(.., length) => length.abs(),
-            Line::Vert(.., length) => length.abs(),
-        }
+impl Segment {
+    /// Whether the segment runs along the x axis. Segments of length zero count as horizontal.
+    fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
     }
 
-    fn distance_to(&self, x: i64, y: i64) -> i64 {
-        match *self {
-            Line::Horz(x0, y0, _) => (x - x0).abs() + (y - y0).abs(),
-            Line::Vert(x0, y0, _) => (x - x0).abs() + (y - y0).abs(),
-        }
+    fn min(&self) -> Point2 {
+        Point2::new(
+            i64::min(self.start.x, self.end.x),
+            i64::min(self.start.y, self.end.y),
+        )
+    }
+
+    fn max(&self) -> Point2 {
+        Point2::new(
+            i64::max(self.start.x, self.end.x),
+            i64::max(self.start.y, self.end.y),
+        )
+    }
+
+    /// The number of steps along the wire to reach `pos`, which must lie on the segment.
+    fn delay_to(&self, pos: Point2) -> i64 {
+        self.delay + self.start.manhattan_to(pos)
     }
 }
 
-fn parse_wire(string: &str) -> Result<Vec<Line>> {
-    let mut wire = vec![];
-    let (mut x, mut y) = (0, 0);
-
-    for part in string.split(',') {
-        let letter = &part[..1];
-        let number = part[1..].parse::<i64>()?;
-
-        if letter == "R" {
-            wire.push(Line::Horz(x, y, number));
-            x += number;
-        } else if letter == "L" {
-            wire.push(Line::Horz(x, y, -number));
-            x -= number;
-        } else if letter == "U" {
-            wire.push(Line::Vert(x, y, number));
-            y += number;
-        } else if letter == "D" {
-            wire.push(Line::Vert(x, y, -number));
-            y -= number;
+/// A position where two different wires meet.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Crossing {
+    pos: Point2,
+    wires: (usize, usize),
+    /// The sum of the steps both wires take to first reach `pos`.
+    delay: i64,
+}
+
+fn parse_wire(wire: usize, line: Span) -> Result<Vec<Segment>> {
+    let mut segments = vec![];
+    let mut pos = Point2::ORIGIN;
+    let mut delay = 0;
+
+    for part in line.split(",") {
+        let (letter, number) = part.split_chars(1)?;
+        let dir = letter
+            .text
+            .parse::<Dir4>()
+            .map_err(|_| letter.error("unknown direction"))?;
+        let length = number.parse::<i64>()?;
+
+        let end = pos + dir.delta(YAxis::Up) * length;
+        segments.push(Segment {
+            wire,
+            start: pos,
+            end,
+            delay,
+        });
+
+        pos = end;
+        delay += length;
+    }
+
+    Ok(segments)
+}
+
+/// Finds every position where a horizontal segment meets a vertical segment of another wire,
+/// sweeping a vertical line from left to right over the segments. Horizontal segments are
+/// active while the line passes over them, and every vertical segment is checked against the
+/// active segments within its range of y.
+fn perpendicular_hits(segments: &[Segment], hit: &mut impl FnMut(Point2, usize, usize)) {
+    // at the same x, segments are added before and removed after checking vertical segments
+    let mut events = vec![];
+    for (i, s) in enumerate(segments) {
+        if s.is_horizontal() {
+            events.push((s.min().x, 0, i));
+            events.push((s.max().x, 2, i));
         } else {
-            println!("warning: unknown direction {}", letter);
+            events.push((s.start.x, 1, i));
         }
     }
+    events.sort_unstable();
 
-    Ok(wire)
-}
+    let mut active = BTreeMap::<i64, Vec<usize>>::new();
+    for (x, kind, i) in events {
+        let s = &segments[i];
 
-fn calculate_intersection(p: Line, q: Line) -> Option<(i64, i64)> {
-    match (p, q) {
-        (Line::Horz(x0, y, h), Line::Vert(x, y0, v)) => {
-            let (x0, x1) = iff!(h > 0, (x0, x0 + h), (x0 + h, x0));
-            let (y0, y1) = iff!(v > 0, (y0, y0 + v), (y0 + v, y0));
+        match kind {
+            0 => active.entry(s.start.y).or_default().push(i),
+            1 => {
+                for (&y, horizontal) in active.range(s.min().y..=s.max().y) {
+                    for &j in horizontal {
+                        if segments[j].wire != s.wire {
+                            hit(Point2::new(x, y), i, j);
+                        }
+                    }
+                }
+            }
+            _ => {
+                let horizontal = active.get_mut(&s.start.y).unwrap();
+                horizontal.retain(|&j| j != i);
 
-            if x >= x0 && x <= x1 && y >= y0 && y <= y1 {
-                Some((x, y))
-            } else {
-                None
+                if horizontal.is_empty() {
+                    active.remove(&s.start.y);
+                }
             }
         }
-        (Line::Vert(..), Line::Horz(..)) => calculate_intersection(q, p),
-        _ => None,
     }
 }
 
-pub fn run(_args: &[&str]) -> Result {
-    let input = read_input("day03")?;
-    let a = parse_wire(&input[0])?;
-    let b = parse_wire(&input[1])?;
+/// Finds every position where two segments of different wires overlap along the same row or
+/// column. Within every row or column the segments are swept in order of their lowest
+/// coordinate, keeping those that reach beyond the current one.
+fn collinear_hits(segments: &[Segment], hit: &mut impl FnMut(Point2, usize, usize)) {
+    for horizontal in &[true, false] {
+        // (row or column, lowest and highest coordinate along it, index)
+        let mut ranges = segments
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_horizontal() == *horizontal)
+            .map(|(i, s)| {
+                let (min, max) = (s.min(), s.max());
+                iff!(
+                    *horizontal,
+                    (min.y, min.x, max.x, i),
+                    (min.x, min.y, max.y, i)
+                )
+            })
+            .collect_vec();
+        ranges.sort_unstable();
+
+        for (_, group) in &ranges.iter().group_by(|r| r.0) {
+            let mut active = Vec::<(i64, usize)>::new();
 
-    let mut closest_dist = std::i64::MAX;
-    let mut fastest_time = std::i64::MAX;
+            for &(line, low, high, i) in group {
+                active.retain(|&(end, _)| end >= low);
 
-    let mut time_a = 0;
-    for &p in &a {
-        let mut time_b = 0;
+                for &(end, j) in &active {
+                    if segments[i].wire == segments[j].wire {
+                        continue;
+                    }
 
-        for &q in &b {
-            if let Some((x, y)) = calculate_intersection(p, q) {
-                let dist = x.abs() + y.abs();
-                closest_dist = i64::min(dist, closest_dist);
+                    for along in low..=i64::min(high, end) {
+                        let pos = iff!(
+                            *horizontal,
+                            Point2::new(along, line),
+                            Point2::new(line, along)
+                        );
+                        hit(pos, i, j);
+                    }
+                }
 
-                let time = time_a + time_b + p.distance_to(x, y) + q.distance_to(x, y);
-                fastest_time = i64::min(time, fastest_time);
+                active.push((high, i));
             }
+        }
+    }
+}
+
+/// Finds every crossing of two different wires, except at the origin where all wires start.
+/// A wire that passes a position more than once is delayed by the first pass only.
+fn find_crossings(segments: &[Segment]) -> Vec<Crossing> {
+    let mut delays = HashMap::<(Point2, usize), i64>::new();
+    let mut crossings = HashSet::new();
 
-            time_b += q.length();
+    let mut hit = |pos: Point2, i: usize, j: usize| {
+        if pos == Point2::ORIGIN {
+            return;
         }
 
-        time_a += p.length();
+        let (p, q) = (&segments[i], &segments[j]);
+        for s in &[p, q] {
+            let delay = delays.entry((pos, s.wire)).or_insert(i64::MAX);
+            *delay = i64::min(*delay, s.delay_to(pos));
+        }
+
+        let wires = iff!(p.wire < q.wire, (p.wire, q.wire), (q.wire, p.wire));
+        crossings.insert((pos, wires));
+    };
+
+    perpendicular_hits(segments, &mut hit);
+    collinear_hits(segments, &mut hit);
+
+    let mut crossings = map(
+        |(pos, (a, b))| Crossing {
+            pos,
+            wires: (a, b),
+            delay: delays[&(pos, a)] + delays[&(pos, b)],
+        },
+        crossings,
+    )
+    .collect_vec();
+
+    crossings.sort_unstable_by_key(|c| (c.pos, c.wires));
+    crossings
+}
+
+/// The colors of the wires. Yellow and cyan are left for the markers.
+const WIRE_COLORS: [Color; 5] = [
+    Color::Red,
+    Color::Blue,
+    Color::Green,
+    Color::Magenta,
+    Color::Gray,
+];
+
+fn hex(color: Color) -> String {
+    let Rgb(r, g, b) = color.into();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Draws the wires as an SVG document, with y pointing up. Crossings are drawn as white dots,
+/// the origin as a yellow square, and the closest and fastest crossings are circled.
+fn draw_wires(
+    wires: &[Vec<Segment>],
+    crossings: &[Crossing],
+    closest: &Crossing,
+    fastest: &Crossing,
+) -> String {
+    let corners = wires
+        .iter()
+        .flatten()
+        .flat_map(|s| vec![s.start, s.end])
+        .chain(Some(Point2::ORIGIN));
+    let (min_x, max_x) = corners.clone().map(|p| p.x).minmax().into_option().unwrap();
+    let (min_y, max_y) = corners.map(|p| -p.y).minmax().into_option().unwrap();
+
+    // leave a margin around the wires, and size the markers relative to the drawing
+    let size = i64::max(max_x - min_x, max_y - min_y).max(1);
+    let margin = size / 50 + 1;
+    let dot = size as f64 / 400.0;
+
+    let mut svg = String::new();
+    let mut line = |text: String| {
+        svg.push_str(&text);
+        svg.push('\n');
+    };
+
+    line(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1000\" height=\"1000\" \
+         viewBox=\"{} {} {} {}\" preserveAspectRatio=\"xMidYMid meet\">",
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + 2 * margin,
+        max_y - min_y + 2 * margin
+    ));
+    line(format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        min_x - margin,
+        min_y - margin,
+        hex(Color::Default)
+    ));
+
+    for (i, wire) in enumerate(wires) {
+        let points = std::iter::once(Point2::ORIGIN)
+            .chain(wire.iter().map(|s| s.end))
+            .map(|p| format!("{},{}", p.x, -p.y))
+            .join(" ");
+
+        line(format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" \
+             vector-effect=\"non-scaling-stroke\" stroke-opacity=\"0.8\">\
+             <title>wire {}</title></polyline>",
+            points,
+            hex(WIRE_COLORS[i % WIRE_COLORS.len()]),
+            i
+        ));
+    }
+
+    for c in crossings {
+        line(format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"><title>wires {} and {} at {}: \
+             distance {}, delay {}</title></circle>",
+            c.pos.x,
+            -c.pos.y,
+            dot,
+            hex(Color::White),
+            c.wires.0,
+            c.wires.1,
+            c.pos,
+            c.pos.manhattan(),
+            c.delay
+        ));
+    }
+
+    line(format!(
+        "<rect x=\"{o}\" y=\"{o}\" width=\"{s}\" height=\"{s}\" fill=\"{}\">\
+         <title>origin</title></rect>",
+        hex(Color::Yellow),
+        o = -2.0 * dot,
+        s = 4.0 * dot
+    ));
+
+    for (c, color, what) in &[
+        (closest, Color::Yellow, "closest"),
+        (fastest, Color::Cyan, "fastest"),
+    ] {
+        line(format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"3\" \
+             vector-effect=\"non-scaling-stroke\"><title>{} crossing at {}</title></circle>",
+            c.pos.x,
+            -c.pos.y,
+            5.0 * dot,
+            hex(*color),
+            what,
+            c.pos
+        ));
+    }
+
+    line("</svg>".to_string());
+    svg
+}
+
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let wires = enumerate(lines(input))
+        .map(|(i, line)| parse_wire(i, line))
+        .collect::<Result<Vec<_>>>()?;
+    if wires.len() < 2 {
+        let message = format!("expected at least 2 wires, found {}", wires.len());
+        return Err(ParseError(message).into());
+    }
+
+    let segments = wires.concat();
+    let crossings = find_crossings(&segments);
+    info!(
+        "{} crossings between {} wires",
+        crossings.len(),
+        wires.len()
+    );
+
+    for c in &crossings {
+        debug!(
+            "wires {} and {} cross at {}: distance {}, delay {}",
+            c.wires.0,
+            c.wires.1,
+            c.pos,
+            c.pos.manhattan(),
+            c.delay
+        );
     }
 
-    println!("answer A: {}", closest_dist);
-    println!("answer B: {}", fastest_time);
+    let closest = crossings
+        .iter()
+        .min_by_key(|c| c.pos.manhattan())
+        .ok_or_else(|| format_err!("the wires do not cross"))?;
+    answer!(A, "{}", closest.pos.manhattan());
+
+    let fastest = crossings.iter().min_by_key(|c| c.delay).unwrap();
+    answer!(B, "{}", fastest.delay);
+
+    if let Some(exporter) = exporter() {
+        exporter.save_svg("day03", &draw_wires(&wires, &crossings, closest, fastest))?;
+    }
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Crossed Wires"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crossings(input: &str) -> Vec<Crossing> {
+        let wires = enumerate(lines(input))
+            .map(|(i, line)| parse_wire(i, line))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        find_crossings(&wires.concat())
+    }
+
+    /// Finds the crossings by walking every wire one step at a time.
+    fn brute_force(input: &str) -> Vec<Crossing> {
+        let mut delays = vec![];
+
+        for (i, line) in enumerate(lines(input)) {
+            let mut first = HashMap::new();
+
+            for s in parse_wire(i, line).unwrap() {
+                let step = Point2::new(
+                    (s.end.x - s.start.x).signum(),
+                    (s.end.y - s.start.y).signum(),
+                );
+                let mut pos = s.start;
+
+                for d in 0..=s.start.manhattan_to(s.end) {
+                    first.entry(pos).or_insert(s.delay + d);
+                    pos += step;
+                }
+            }
+
+            delays.push(first);
+        }
+
+        let mut crossings = vec![];
+        for (a, b) in (0..delays.len()).tuple_combinations() {
+            for (&pos, &delay) in &delays[a] {
+                if let Some(&other) = delays[b].get(&pos).filter(|_| pos != Point2::ORIGIN) {
+                    crossings.push(Crossing {
+                        pos,
+                        wires: (a, b),
+                        delay: delay + other,
+                    });
+                }
+            }
+        }
+
+        crossings.sort_unstable_by_key(|c| (c.pos, c.wires));
+        crossings
+    }
+
+    fn closest_and_fastest(input: &str) -> (i64, i64) {
+        let crossings = crossings(input);
+        assert_eq!(crossings, brute_force(input));
+
+        (
+            crossings.iter().map(|c| c.pos.manhattan()).min().unwrap(),
+            crossings.iter().map(|c| c.delay).min().unwrap(),
+        )
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(closest_and_fastest("R8,U5,L5,D3\nU7,R6,D4,L4"), (6, 30));
+        assert_eq!(
+            closest_and_fastest(
+                "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83"
+            ),
+            (159, 610)
+        );
+        assert_eq!(
+            closest_and_fastest(
+                "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\n\
+                 U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"
+            ),
+            (135, 410)
+        );
+    }
+
+    #[test]
+    fn collinear_overlaps() {
+        // the second wire runs along the first one from x = 2 to x = 7
+        let found = crossings("R10\nU1,R2,D1,R5");
+        assert_eq!(
+            map(|c| c.pos.x, &found).collect_vec(),
+            (2..=7).collect_vec()
+        );
+        assert_eq!(found[0].delay, 2 + 4);
+        assert_eq!(found, brute_force("R10\nU1,R2,D1,R5"));
+
+        // in opposite directions, and passing the origin again
+        let found = crossings("R10\nL3,R8");
+        assert_eq!(
+            map(|c| c.pos.x, &found).collect_vec(),
+            (1..=5).collect_vec()
+        );
+        assert_eq!(found[0].delay, 1 + 7);
+        assert_eq!(found, brute_force("R10\nL3,R8"));
+
+        // vertical segments on the same column
+        assert_eq!(crossings("U5\nR1,U2,L1,U5").len(), 4);
+    }
+
+    #[test]
+    fn touching_endpoints() {
+        assert_eq!(
+            crossings("R5\nU2,R5,D2"),
+            [Crossing {
+                pos: Point2::new(5, 0),
+                wires: (0, 1),
+                delay: 5 + 9,
+            }]
+        );
+
+        // the end of one wire touches the middle of the other
+        assert_eq!(closest_and_fastest("R5\nU3,R2,D3"), (2, 2 + 8));
+        assert_eq!(closest_and_fastest("U3,R2,D3\nR5"), (2, 2 + 8));
+    }
+
+    #[test]
+    fn origin_is_excluded() {
+        assert_eq!(crossings("U3,D6\nR3,L6"), []);
+        assert_eq!(crossings("R3\nR3"), brute_force("R3\nR3"));
+        assert_eq!(crossings("R3\nR3").len(), 3);
+    }
+
+    #[test]
+    fn more_than_two_wires() {
+        let input = "R8,U5,L5,D3\nU7,R6,D4,L4\nR3,U10\nL2,U3,R12";
+        let found = crossings(input);
+        assert_eq!(found, brute_force(input));
+
+        let pairs = found
+            .iter()
+            .map(|c| c.wires)
+            .unique()
+            .sorted()
+            .collect_vec();
+        assert_eq!(pairs, [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn random_wires() {
+        // a fixed linear congruential generator, so that failures can be reproduced
+        let mut seed = 12345u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+
+        for _ in 0..200 {
+            let wires = (0..2 + next(3))
+                .map(|_| {
+                    (0..1 + next(8))
+                        .map(|_| format!("{}{}", ['U', 'D', 'L', 'R'][next(4) as usize], next(6)))
+                        .join(",")
+                })
+                .join("\n");
+
+            assert_eq!(crossings(&wires), brute_force(&wires), "{}", wires);
+        }
+    }
+}
@@ -1,7 +1,74 @@
 use crate::common::*;
+use crate::solution::Solution;
 use itertools::Itertools;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
-fn is_valid_password(code: i64, exact: bool) -> bool {
+/// A rule on the runs of equal digits in a password: some run must be between `min` and `max`
+/// digits long, inclusive.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct RunRule {
+    min: usize,
+    max: usize,
+}
+
+impl RunRule {
+    fn at_least(min: usize) -> Self {
+        Self {
+            min,
+            max: usize::MAX,
+        }
+    }
+
+    fn exactly(len: usize) -> Self {
+        Self { min: len, max: len }
+    }
+
+    fn accepts(self, run: usize) -> bool {
+        run >= self.min && run <= self.max
+    }
+
+    /// Returns a run length that is accepted exactly if `run` is, and that does not grow
+    /// without bound.
+    fn cap(self, run: usize) -> usize {
+        usize::min(run, iff!(self.max == usize::MAX, self.min, self.max + 1))
+    }
+}
+
+impl FromStr for RunRule {
+    type Err = ParseError;
+
+    /// Parses `N` (exactly N), `N+` (at least N) or `N-M` (between N and M).
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let number = |s: &str| s.parse::<usize>().ok().filter(|&n| n > 0);
+
+        let rule = if let Some(min) = s.strip_suffix('+') {
+            number(min).map(RunRule::at_least)
+        } else if let Some((min, max)) = s.split_once('-') {
+            match (number(min), number(max)) {
+                (Some(min), Some(max)) if min <= max => Some(RunRule { min, max }),
+                _ => None,
+            }
+        } else {
+            number(s).map(RunRule::exactly)
+        };
+
+        rule.ok_or_else(|| ParseError(format!("invalid run rule {:?}", s)))
+    }
+}
+
+impl fmt::Display for RunRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.min, self.max) {
+            (min, usize::MAX) => write!(f, "{}+", min),
+            (min, max) if min == max => write!(f, "{}", min),
+            (min, max) => write!(f, "{}-{}", min, max),
+        }
+    }
+}
+
+fn is_valid_password(code: u64, rule: RunRule) -> bool {
     let buffer = format!("{}", code).into_bytes();
 
     for (a, b) in buffer.iter().zip(&buffer[1..]) {
@@ -11,7 +78,7 @@ fn is_valid_password(code: i64, exact: bool) -> bool {
     }
 
     for (_, group) in &buffer.iter().group_by(|&x| x) {
-        if iff!(exact, group.count() == 2, group.count() >= 2) {
+        if rule.accepts(group.count()) {
             return true;
         }
     }
@@ -19,23 +86,264 @@ fn is_valid_password(code: i64, exact: bool) -> bool {
     false
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let range = read_input("day04")?[0]
-        .split('-')
-        .map(|x| x.parse::<i64>())
-        .collect::<Result<Vec<_>, _>>()?;
+/// Counts the passwords with non-decreasing digits and a run accepted by `rule`, by choosing
+/// the digits one at a time instead of trying every number.
+struct Counter {
+    rule: RunRule,
+    memo: HashMap<(usize, u8, usize, bool), u64>,
+}
+
+impl Counter {
+    fn new(rule: RunRule) -> Self {
+        Self {
+            rule,
+            memo: default(),
+        }
+    }
+
+    /// Counts the ways to append `remaining` digits to a password ending in a run of `run`
+    /// digits `last`, where `found` tells whether an earlier run was accepted.
+    fn count_suffixes(&mut self, remaining: usize, last: u8, run: usize, found: bool) -> u64 {
+        if remaining == 0 {
+            return (found || self.rule.accepts(run)) as u64;
+        }
+
+        let key = (remaining, last, run, found);
+        if let Some(&count) = self.memo.get(&key) {
+            return count;
+        }
+
+        let mut count = self.count_suffixes(remaining - 1, last, self.rule.cap(run + 1), found);
+        for digit in (last + 1)..=9 {
+            let found = found || self.rule.accepts(run);
+            count += self.count_suffixes(remaining - 1, digit, 1, found);
+        }
+
+        self.memo.insert(key, count);
+        count
+    }
+
+    /// Counts the valid passwords in `0..=limit`.
+    fn count_up_to(&mut self, limit: u64) -> u64 {
+        let digits = map(|b| b - b'0', limit.to_string().into_bytes()).collect_vec();
+        let n = digits.len();
+
+        // zero is the only password starting with a zero
+        let mut count = self.rule.accepts(1) as u64;
+
+        // passwords with fewer digits than the limit
+        for len in 1..n {
+            for first in 1..=9 {
+                count += self.count_suffixes(len - 1, first, 1, false);
+            }
+        }
+
+        // passwords with as many digits as the limit, which share a prefix with the limit and
+        // then have a smaller digit
+        let (mut last, mut run, mut found) = (1, 0, false);
+        for (i, &limit_digit) in enumerate(&digits) {
+            for digit in last..limit_digit {
+                let (run, found) = iff!(
+                    digit == last && i > 0,
+                    (self.rule.cap(run + 1), found),
+                    (1, found || (i > 0 && self.rule.accepts(run)))
+                );
+                count += self.count_suffixes(n - i - 1, digit, run, found);
+            }
+
+            if limit_digit < last {
+                return count;
+            }
+
+            if limit_digit == last && i > 0 {
+                run = self.rule.cap(run + 1);
+            } else {
+                found = found || (i > 0 && self.rule.accepts(run));
+                run = 1;
+            }
+            last = limit_digit;
+        }
+
+        // the limit itself
+        count + (found || self.rule.accepts(run)) as u64
+    }
+
+    /// Counts the valid passwords in `low..=high`.
+    fn count_range(&mut self, low: u64, high: u64) -> u64 {
+        match low.checked_sub(1) {
+            _ if low > high => 0,
+            Some(below) => self.count_up_to(high) - self.count_up_to(below),
+            None => self.count_up_to(high),
+        }
+    }
+}
+
+/// Ranges on which the counter is compared against trying every number: the given range, the
+/// small numbers, the ranges around every power of ten and the range at the end of `u64`.
+fn check_ranges(low: u64, high: u64) -> Vec<(u64, u64)> {
+    let mut ranges = vec![(low, high), (0, 100_000)];
+    for exp in 1..=19 {
+        let p = 10u64.pow(exp);
+        ranges.push((p.saturating_sub(1000), p + 1000));
+    }
+    ranges.push((u64::MAX - 10_000, u64::MAX));
+    ranges
+}
+
+/// Counts the valid passwords in `low..=high` by trying every number.
+fn brute_force(low: u64, high: u64, rule: RunRule) -> u64 {
+    (low..=high).filter(|&p| is_valid_password(p, rule)).count() as u64
+}
+
+/// Compares the counter against trying every number, for the rules given on the command line.
+fn verify(low: u64, high: u64, rules: &[RunRule]) -> Result {
+    let ranges = check_ranges(low, high);
+
+    for &rule in rules {
+        let mut counter = Counter::new(rule);
+
+        for &(low, high) in &ranges {
+            let expected = brute_force(low, high, rule);
+            let count = counter.count_range(low, high);
+
+            if count != expected {
+                bail!(
+                    "rule {} on {}-{}: counted {}, expected {}",
+                    rule,
+                    low,
+                    high,
+                    count,
+                    expected
+                );
+            }
+        }
+
+        info!("rule {}: verified {} ranges", rule, ranges.len());
+    }
+
+    Ok(())
+}
+
+pub fn run(input: &str, args: &[&str]) -> Result {
+    let [low, high] = Span::new(input).trim().fields("-")?;
+    let (low, high) = (low.parse::<u64>()?, high.parse::<u64>()?);
+
+    let mut rules = vec![RunRule::at_least(2), RunRule::exactly(2)];
+    let mut check = false;
+    let mut iter = args.iter().copied();
 
-    let count = (range[0]..=range[1])
-        .filter(|&p| is_valid_password(p, false))
-        .count();
+    while let Some(arg) = iter.next() {
+        match arg {
+            "--verify" => check = true,
+            "--rule" => match iter.next() {
+                Some(rule) => rules.push(rule.parse()?),
+                None => bail!("missing argument for --rule"),
+            },
+            _ => bail!("usage: 4 [--rule N|N+|N-M]... [--verify]"),
+        }
+    }
 
-    println!("answer A: {}", count);
+    if check {
+        verify(low, high, &rules)?;
+    }
 
-    let count = (range[0]..=range[1])
-        .filter(|&p| is_valid_password(p, true))
-        .count();
+    answer!(A, "{}", Counter::new(rules[0]).count_range(low, high));
+    answer!(B, "{}", Counter::new(rules[1]).count_range(low, high));
 
-    println!("answer B: {}", count);
+    for &rule in &rules[2..] {
+        outln!(
+            "rule {}: {}",
+            rule,
+            Counter::new(rule).count_range(low, high)
+        );
+    }
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Secure Container"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Vec<RunRule> {
+        map(
+            |s: &str| s.parse().unwrap(),
+            ["2", "2+", "2-3", "1", "3+", "1-6"],
+        )
+        .collect()
+    }
+
+    #[test]
+    fn parse_rules() {
+        assert_eq!("2".parse::<RunRule>().unwrap(), RunRule::exactly(2));
+        assert_eq!("2+".parse::<RunRule>().unwrap(), RunRule::at_least(2));
+        assert_eq!(
+            "2-3".parse::<RunRule>().unwrap(),
+            RunRule { min: 2, max: 3 }
+        );
+
+        for s in &["", "0", "+", "3-2", "1-", "x"] {
+            assert!(s.parse::<RunRule>().is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn small_ranges() {
+        for rule in rules() {
+            let mut counter = Counter::new(rule);
+            let prefix = map(|n| brute_force(0, n, rule), 0..=200).collect_vec();
+
+            for low in 0..=200 {
+                for high in low..=200 {
+                    let expected =
+                        prefix[high as usize] - iff!(low > 0, prefix[low as usize - 1], 0);
+                    assert_eq!(
+                        counter.count_range(low, high),
+                        expected,
+                        "{} {}-{}",
+                        rule,
+                        low,
+                        high
+                    );
+                }
+            }
+
+            assert_eq!(counter.count_range(5, 4), 0);
+        }
+    }
+
+    #[test]
+    fn ranges_around_powers_of_ten() {
+        for rule in rules() {
+            let mut counter = Counter::new(rule);
+
+            for (low, high) in check_ranges(123456, 654321) {
+                let expected = brute_force(low, high, rule);
+                assert_eq!(
+                    counter.count_range(low, high),
+                    expected,
+                    "{} {}-{}",
+                    rule,
+                    low,
+                    high
+                );
+            }
+        }
+    }
+}
@@ -1,16 +1,37 @@
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let original = parse_program("day05")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let original = parse_program(input)?;
 
     let mut program = original.clone();
     let outputs = program.run(&[1])?;
-    println!("answer A: {:?}", outputs);
+    answer!(A, "{:?}", outputs);
 
     let mut program = original;
     let outputs = program.run(&[5])?;
-    println!("answer B: {:?}", outputs);
+    answer!(B, "{:?}", outputs);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Sunny with a Chance of Asteroids"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
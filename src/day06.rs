@@ -1,55 +1,365 @@
+use crate::common::parse::lines;
 use crate::common::*;
+use crate::image::exporter;
+use crate::solution::Solution;
 use std::collections::HashMap;
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    // parse input
-    let mut orbits = HashMap::new();
+/// The bodies of an orbit map, where every body except the root orbits exactly one other body.
+struct OrbitMap {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    parent: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    depth: Vec<usize>,
+}
+
+impl OrbitMap {
+    fn parse(input: &str) -> Result<Self> {
+        let mut map = OrbitMap {
+            names: vec![],
+            index: default(),
+            parent: vec![],
+            children: vec![],
+            depth: vec![],
+        };
+
+        // the line on which the orbit of every body is given
+        let mut orbit_lines = HashMap::new();
+
+        for line in lines(input) {
+            let (center, body) = line.split_once(")")?;
+            let (center, body) = (map.add(center.text), map.add(body.text));
+
+            if let Some(other) = map.parent[body] {
+                return Err(line.error(format!(
+                    "{} already orbits {}",
+                    map.names[body], map.names[other]
+                )));
+            }
+
+            map.parent[body] = Some(center);
+            map.children[center].push(body);
+            orbit_lines.insert(body, line);
+        }
+
+        let roots = (0..map.names.len())
+            .filter(|&b| map.parent[b].is_none())
+            .collect_vec();
+        if roots.len() > 1 {
+            return Err(ParseError(format!(
+                "expected one body at the center, found {}: {}",
+                roots.len(),
+                roots.iter().map(|&b| &map.names[b]).join(", ")
+            ))
+            .into());
+        }
 
-    for line in read_input("day06")? {
-        if let Some(index) = line.find(')') {
-            let src = line[..index].to_string();
-            let dst = line[(index + 1)..].to_string();
+        // assign depths from the root down, so bodies on a cycle are never reached
+        let mut reached = vec![false; map.names.len()];
+        let mut stack = roots;
+        while let Some(body) = stack.pop() {
+            reached[body] = true;
 
-            orbits.insert(dst.clone(), src.clone());
+            for &child in &map.children[body] {
+                map.depth[child] = map.depth[body] + 1;
+                stack.push(child);
+            }
+        }
+
+        if let Some(mut body) = reached.iter().position(|&r| !r) {
+            // bodies that are not reached all have a center, and after as many steps as there
+            // are bodies the walk towards the center is on the cycle
+            for _ in 0..map.names.len() {
+                body = map.parent[body].unwrap();
+            }
+
+            let message = format!("{} is part of an orbit cycle", map.names[body]);
+            return Err(orbit_lines[&body].error(message));
         }
+
+        Ok(map)
+    }
+
+    /// Returns the index of the body with the given name, adding it if it is new.
+    fn add(&mut self, name: &str) -> usize {
+        if let Some(&body) = self.index.get(name) {
+            return body;
+        }
+
+        let body = self.names.len();
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), body);
+        self.parent.push(None);
+        self.children.push(vec![]);
+        self.depth.push(0);
+        body
     }
 
-    // for each planet, traverse back to the root and count the number of hops
-    let mut total_orbits = 0;
-    for origin in orbits.keys() {
-        let mut current = origin;
+    fn body(&self, name: &str) -> Result<usize> {
+        let body = self.index.get(name).copied();
+        body.ok_or_else(|| ParseError(format!("unknown body {:?}", name)).into())
+    }
+
+    /// Returns the number of direct and indirect orbits of all bodies.
+    fn total_orbits(&self) -> usize {
+        sum(self.depth.iter().copied())
+    }
+
+    /// Returns the number of bodies that `body` orbits directly or indirectly.
+    fn depth(&self, body: usize) -> usize {
+        self.depth[body]
+    }
 
-        while let Some(t) = orbits.get(current) {
-            total_orbits += 1;
-            current = t;
+    /// Returns the deepest body that both `a` and `b` orbit, or that is `a` or `b` itself.
+    fn common_ancestor(&self, mut a: usize, mut b: usize) -> usize {
+        while self.depth(a) > self.depth(b) {
+            a = self.parent[a].unwrap();
         }
+
+        while self.depth(b) > self.depth(a) {
+            b = self.parent[b].unwrap();
+        }
+
+        while a != b {
+            a = self.parent[a].unwrap();
+            b = self.parent[b].unwrap();
+        }
+
+        a
     }
 
-    println!("answer A: {}", total_orbits);
+    /// Returns the number of orbital transfers needed to move from `a` to `b`.
+    fn transfers(&self, a: usize, b: usize) -> usize {
+        let common = self.common_ancestor(a, b);
+        self.depth(a) + self.depth(b) - 2 * self.depth(common)
+    }
+
+    /// Returns the bodies visited when moving from `a` to `b`, including both.
+    fn path(&self, a: usize, b: usize) -> Vec<usize> {
+        let common = self.common_ancestor(a, b);
+        let up = |mut body: usize| {
+            let mut path = vec![body];
+            while body != common {
+                body = self.parent[body].unwrap();
+                path.push(body);
+            }
+            path
+        };
+
+        let mut path = up(a);
+        let down = up(b);
+        path.extend(down.into_iter().rev().skip(1));
+        path
+    }
 
-    // get both backwards and forward edges
-    let mut neighbors = HashMap::<&str, Vec<&str>>::new();
-    for (dst, src) in &orbits {
-        neighbors.entry(src).or_default().push(dst);
-        neighbors.entry(dst).or_default().push(src);
+    /// Returns the body that `name` orbits.
+    fn center_of(&self, name: &str) -> Result<usize> {
+        let center = self.parent[self.body(name)?];
+        center.ok_or_else(|| format_err!("{} does not orbit anything", name).into())
     }
 
-    // basic depth-first-search implementation
-    let mut queue = vec![("YOU", 0)];
-    let mut visited = HashMap::<&str, u32>::new();
+    /// Draws the map as a Graphviz digraph with an edge from every body to the bodies orbiting
+    /// it. The bodies and orbits along `path` are highlighted, and runs of at least
+    /// `MIN_CHAIN` bodies that have a single satellite and are not on the path are drawn as
+    /// one node.
+    fn to_dot(&self, path: &[usize]) -> String {
+        let mut on_path = vec![false; self.names.len()];
+        for &body in path {
+            on_path[body] = true;
+        }
+
+        let collapsible =
+            |b: usize| !on_path[b] && self.parent[b].is_some() && self.children[b].len() == 1;
+
+        let mut dot = String::new();
+        let mut line = |text: String| {
+            dot.push_str(&text);
+            dot.push('\n');
+        };
+
+        line("digraph orbits {".to_string());
+        line("    rankdir=LR;".to_string());
+        line("    node [shape=ellipse, fontname=\"monospace\"];".to_string());
+
+        // every body is visited from the node drawn for the body it orbits
+        let mut stack =
+            map(|b| (b, None), self.parent.iter().positions(Option::is_none)).collect_vec();
 
-    while let Some((current, dist)) = queue.pop() {
-        for neighbor in &neighbors[current] {
-            if !visited.contains_key(&**neighbor) {
-                visited.insert(neighbor, dist + 1);
-                queue.push((neighbor, dist + 1));
+        while let Some((body, from)) = stack.pop() {
+            let mut last = body;
+            let mut length = 1;
+            while collapsible(last) && collapsible(self.children[last][0]) {
+                last = self.children[last][0];
+                length += 1;
+            }
+
+            let (node, highlight) = if length >= MIN_CHAIN {
+                let label = format!("{} .. {}", self.names[body], self.names[last]);
+                let node = format!("{:?}", label);
+                line(format!(
+                    "    {} [label={:?}, shape=box, style=dashed];",
+                    node,
+                    format!("{}\n({} bodies)", label, length)
+                ));
+                (node, false)
+            } else {
+                last = body;
+                let node = format!("{:?}", self.names[body]);
+                if on_path[body] {
+                    let color = iff!(
+                        body == path[0] || body == path[path.len() - 1],
+                        "gold",
+                        "salmon"
+                    );
+                    line(format!("    {} [style=filled, fillcolor={}];", node, color));
+                }
+                (node, on_path[body])
+            };
+
+            if let Some((from, from_highlight)) = from {
+                let style = iff!(from_highlight && highlight, " [color=red, penwidth=3]", "");
+                line(format!("    {} -> {}{};", from, node, style));
+            }
+
+            for &child in self.children[last].iter().rev() {
+                stack.push((child, Some((node.clone(), highlight))));
             }
         }
+
+        line("}".to_string());
+        dot
     }
+}
+
+/// The shortest run of bodies that is collapsed into a single node when drawing the map.
+const MIN_CHAIN: usize = 3;
+
+pub fn run(input: &str, args: &[&str]) -> Result {
+    let (from, to) = match args {
+        [] => ("YOU", "SAN"),
+        [from, to] => (*from, *to),
+        _ => bail!("usage: 6 [FROM TO]"),
+    };
+
+    let map = OrbitMap::parse(input)?;
+    answer!(A, "{}", map.total_orbits());
 
-    // -2 since YOU and SAN are not planets some we do'nt need the
-    // first and final transfer
-    println!("answer B: {}", visited["SAN"] - 2);
+    // the transfers are between the bodies that FROM and TO orbit
+    let (a, b) = (map.center_of(from)?, map.center_of(to)?);
+    let path = map.path(a, b);
+    debug!(
+        "transfers: {}",
+        path.iter().map(|&body| &map.names[body]).join(" -> ")
+    );
+    debug!(
+        "{} orbits at depth {}, {} at depth {}, meeting at {}",
+        from,
+        map.depth(a) + 1,
+        to,
+        map.depth(b) + 1,
+        map.names[map.common_ancestor(a, b)]
+    );
+
+    answer!(B, "{}", map.transfers(a, b));
+
+    if let Some(exporter) = exporter() {
+        let path = map.path(map.body(from)?, map.body(to)?);
+        exporter.save_dot("day06", &map.to_dot(&path))?;
+    }
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Universal Orbit Map"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN";
+
+    fn names(orbits: &OrbitMap, path: &[usize]) -> Vec<String> {
+        map(|&body| orbits.names[body].clone(), path).collect()
+    }
+
+    fn error(input: &str) -> String {
+        OrbitMap::parse(input).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn example() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        assert_eq!(map.total_orbits(), 54);
+
+        let (a, b) = (map.center_of("YOU").unwrap(), map.center_of("SAN").unwrap());
+        assert_eq!(map.transfers(a, b), 4);
+        assert_eq!(names(&map, &map.path(a, b)), ["K", "J", "E", "D", "I"]);
+        assert_eq!(map.names[map.common_ancestor(a, b)], "D");
+
+        // without YOU and SAN the example is the one of part one
+        let input = EXAMPLE.lines().take(11).join("\n");
+        assert_eq!(OrbitMap::parse(&input).unwrap().total_orbits(), 42);
+    }
+
+    #[test]
+    fn ancestors() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        let (b, e, l) = (
+            map.body("B").unwrap(),
+            map.body("E").unwrap(),
+            map.body("L").unwrap(),
+        );
+
+        assert_eq!(map.common_ancestor(b, l), b);
+        assert_eq!(map.transfers(b, l), 6);
+        assert_eq!(map.transfers(l, b), 6);
+        assert_eq!(
+            names(&map, &map.path(b, l)),
+            ["B", "C", "D", "E", "J", "K", "L"]
+        );
+        assert_eq!(names(&map, &map.path(l, e)), ["L", "K", "J", "E"]);
+
+        assert_eq!(map.transfers(e, e), 0);
+        assert_eq!(names(&map, &map.path(e, e)), ["E"]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("COM)A\nB)C\nC)B"),
+            "line 3, column 1: B is part of an orbit cycle: \"C)B\""
+        );
+        assert_eq!(
+            error("COM)A\nX)Y\nA)B"),
+            "expected one body at the center, found 2: COM, X"
+        );
+        assert_eq!(
+            error("COM)A\nCOM)B\nB)A"),
+            "line 3, column 1: A already orbits COM: \"B)A\""
+        );
+
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            map.body("X").err().unwrap().to_string(),
+            "unknown body \"X\""
+        );
+        assert_eq!(
+            map.center_of("COM").err().unwrap().to_string(),
+            "COM does not orbit anything"
+        );
+    }
+}
@@ -1,72 +1,350 @@
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
+use std::collections::VecDeque;
+use std::str::FromStr;
 
-fn permutations(n: usize) -> Vec<Vec<usize>> {
-    if n == 1 {
-        return vec![vec![0]];
+/// Returns all orderings of `items` in lexicographic order, starting from the sorted one. Equal
+/// items are not told apart, so every distinct ordering is returned once. The orderings are
+/// generated one at a time.
+fn permutations<T: Ord + Clone>(items: &[T]) -> Permutations<T> {
+    let mut items = items.to_vec();
+    items.sort();
+
+    Permutations { next: Some(items) }
+}
+
+struct Permutations<T> {
+    next: Option<Vec<T>>,
+}
+
+impl<T: Ord + Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        let current = self.next.take()?;
+        let mut next = current.clone();
+
+        // find the last ascent, swap it with the last larger item and reverse the tail
+        if let Some(i) = (1..next.len()).rev().find(|&i| next[i - 1] < next[i]) {
+            let j = (i..next.len())
+                .rev()
+                .find(|&j| next[i - 1] < next[j])
+                .unwrap();
+            next.swap(i - 1, j);
+            next[i..].reverse();
+            self.next = Some(next);
+        }
+
+        Some(current)
+    }
+}
+
+/// A network of amplifiers that all run the same program. Every amplifier first reads its
+/// phase setting, and then reads the signals sent to it. The input signal is sent to the first
+/// amplifier, and the signal of the network is the last one sent by the last amplifier.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Network {
+    /// The amplifiers that receive the signals sent by each amplifier.
+    targets: Vec<Vec<usize>>,
+}
+
+impl Network {
+    /// Amplifiers that each send their signals to the next one.
+    fn chain(n: usize) -> Self {
+        Self {
+            targets: map(|i| iff!(i + 1 < n, vec![i + 1], vec![]), 0..n).collect(),
+        }
+    }
+
+    /// A chain in which the last amplifier also sends its signals back to the first one.
+    fn ring(n: usize) -> Self {
+        Self {
+            targets: map(|i| vec![(i + 1) % n], 0..n).collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.targets.len()
     }
 
-    let mut output = vec![];
-    let others = permutations(n - 1);
-    for i in 0..n {
-        for rest in &others {
-            let mut p = vec![i];
+    /// Runs the network with the given phase settings until every amplifier has halted.
+    /// Amplifiers take turns running until they halt or wait for a signal, and the network is
+    /// blocked if all amplifiers that have not halted wait for a signal that never comes.
+    fn run(&self, program: &Program, phases: &[i64], signal: i64) -> Result<i64> {
+        let n = self.len();
+        let mut amps = vec![program.clone(); n];
+        let mut queues = map(|&p| VecDeque::from(vec![p]), phases).collect_vec();
+        let mut halted = vec![false; n];
+        let mut result = None;
+        queues[0].push_back(signal);
 
-            for &v in rest {
-                p.push(iff!(v >= i, v + 1, v));
+        while !all(&halted, |&h| h) {
+            let mut progress = false;
+
+            for i in 0..n {
+                while !halted[i] {
+                    let before = queues[i].len();
+                    let queue = &mut queues[i];
+                    let state = amps[i].resume(std::iter::from_fn(|| queue.pop_front()))?;
+                    progress |= queues[i].len() != before;
+
+                    match state {
+                        ExecState::Output(value) => {
+                            for &j in &self.targets[i] {
+                                queues[j].push_back(value);
+                            }
+
+                            if i == n - 1 {
+                                result = Some(value);
+                            }
+                            progress = true;
+                        }
+                        ExecState::Halted => {
+                            halted[i] = true;
+                            progress = true;
+                        }
+                        ExecState::Input => break,
+                    }
+                }
             }
 
-            output.push(p);
+            if !progress {
+                let waiting = (0..n).filter(|&i| !halted[i]).join(", ");
+                bail!(
+                    "network is blocked, amplifiers {} wait for a signal",
+                    waiting
+                );
+            }
         }
+
+        result.ok_or_else(|| format_err!("the last amplifier sent no signal").into())
     }
 
-    output
+    /// Tries every order of the phase settings, and returns the order that gives the strongest
+    /// signal together with that signal.
+    fn best_phases(&self, program: &Program, phases: &[i64]) -> Result<(Vec<i64>, i64)> {
+        if self.len() == 0 {
+            bail!("the network has no amplifiers");
+        } else if phases.len() != self.len() {
+            bail!(
+                "expected {} phase settings, found {}",
+                self.len(),
+                phases.len()
+            );
+        }
+
+        let mut best: Option<(Vec<i64>, i64)> = None;
+
+        for order in permutations(phases) {
+            let signal = self.run(program, &order, 0)?;
+
+            if best.as_ref().is_none_or(|(_, b)| signal > *b) {
+                best = Some((order, signal));
+            }
+        }
+
+        Ok(best.unwrap())
+    }
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let original = parse_program("day07")?;
-    let mut max_value = 0;
+impl FromStr for Network {
+    type Err = Error;
 
-    for order in permutations(5) {
-        let mut value = 0;
+    /// Parses a list of connections such as `0>1,0>2,1>3,2>3`, where the amplifier with the
+    /// highest number is the last one.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut edges = vec![];
+        for edge in Span::new(s).split(",") {
+            let [from, to] = edge.fields(">")?;
+            edges.push((from.parse::<usize>()?, to.parse::<usize>()?));
+        }
 
-        for i in order {
-            let output = original.clone().run(&[i as _, value])?;
-            value = output[0];
+        let n = edges
+            .iter()
+            .map(|&(a, b)| usize::max(a, b) + 1)
+            .max()
+            .unwrap_or(0);
+        let mut targets = vec![vec![]; n];
+        for (from, to) in edges {
+            targets[from].push(to);
         }
 
-        max_value = i64::max(value, max_value);
+        Ok(Self { targets })
     }
+}
 
-    println!("answer A: {}", max_value);
+/// The network given on the command line. Chains and rings get one amplifier for every
+/// phase setting.
+enum Topology {
+    Chain,
+    Ring,
+    Custom(Network),
+}
 
-    max_value = 0;
+impl Topology {
+    fn network(self, phases: usize) -> Network {
+        match self {
+            Topology::Chain => Network::chain(phases),
+            Topology::Ring => Network::ring(phases),
+            Topology::Custom(network) => network,
+        }
+    }
+}
 
-    for order in permutations(5) {
-        let mut programs = vec![];
-        let mut value = 0;
+impl FromStr for Topology {
+    type Err = Error;
 
-        for i in order {
-            let mut program = original.clone();
-            program.resume(Some(i as i64 + 5))?;
-            programs.push(program);
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "chain" => Topology::Chain,
+            "ring" => Topology::Ring,
+            _ => Topology::Custom(s.parse()?),
+        })
+    }
+}
+
+pub fn run(input: &str, args: &[&str]) -> Result {
+    let program = parse_program(input)?;
+
+    let (order, signal) = Network::chain(5).best_phases(&program, &[0, 1, 2, 3, 4])?;
+    debug!("chain: phases {:?}", order);
+    answer!(A, "{}", signal);
+
+    let (order, signal) = Network::ring(5).best_phases(&program, &[5, 6, 7, 8, 9])?;
+    debug!("ring: phases {:?}", order);
+    answer!(B, "{}", signal);
+
+    let mut topology = None;
+    let mut phases = None;
+    let mut iter = args.iter().copied();
+
+    while let Some(arg) = iter.next() {
+        match (arg, iter.next()) {
+            ("--network", Some(spec)) => topology = Some(spec.parse::<Topology>()?),
+            ("--phases", Some(list)) => phases = Some(Span::new(list).parse_list::<i64>(",")?),
+            _ => bail!("usage: 7 [--network chain|ring|A>B,...] [--phases P,...]"),
         }
+    }
 
-        let mut halted = false;
-        while !halted {
-            for program in &mut programs {
-                match program.resume(Some(value))? {
-                    ExecState::Output(v) => value = v,
-                    ExecState::Input => panic!("need input?"),
-                    ExecState::Halted => halted = true,
-                }
-            }
+    if topology.is_some() || phases.is_some() {
+        let phases = phases.unwrap_or_else(|| vec![0, 1, 2, 3, 4]);
+        let network = topology.unwrap_or(Topology::Chain).network(phases.len());
+
+        let (order, signal) = network.best_phases(&program, &phases)?;
+        outln!("phases {:?}: signal {}", order, signal);
+    }
+
+    Ok(())
+}
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        7
+    }
+
+    fn title(&self) -> &'static str {
+        "Amplification Circuit"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best(network: Network, program: &str, phases: &[i64]) -> (Vec<i64>, i64) {
+        let program = parse_program(program).unwrap();
+        network.best_phases(&program, phases).unwrap()
+    }
+
+    #[test]
+    fn permutation_counts() {
+        for (n, count) in [(0, 1), (1, 1), (5, 120)] {
+            let items = (0..n).collect_vec();
+            let orders = permutations(&items).collect_vec();
+            assert_eq!(orders.len(), count);
+            assert_eq!(orders.iter().unique().count(), count);
+            assert!(orders.windows(2).all(|w| w[0] < w[1]));
+            assert!(orders.iter().all(|order| order.iter().sorted().eq(&items)));
         }
 
-        max_value = i64::max(value, max_value);
+        let orders = permutations(&[2, 1, 1]).collect_vec();
+        assert_eq!(orders, [[1, 1, 2], [1, 2, 1], [2, 1, 1]]);
     }
 
-    println!("answer B: {}", max_value);
+    #[test]
+    fn chain_examples() {
+        let examples = [
+            (
+                "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0",
+                [4, 3, 2, 1, 0],
+                43210,
+            ),
+            (
+                "3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0",
+                [0, 1, 2, 3, 4],
+                54321,
+            ),
+            (
+                "3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,\
+                 31,31,4,31,99,0,0,0",
+                [1, 0, 4, 3, 2],
+                65210,
+            ),
+        ];
 
-    Ok(())
+        for (program, order, signal) in examples {
+            let result = best(Network::chain(5), program, &[0, 1, 2, 3, 4]);
+            assert_eq!(result, (order.to_vec(), signal));
+        }
+    }
+
+    #[test]
+    fn ring_examples() {
+        let examples = [
+            (
+                "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,\
+                 0,0,5",
+                [9, 8, 7, 6, 5],
+                139629729,
+            ),
+            (
+                "3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,\
+                 12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,\
+                 99,0,0,0,0,10",
+                [9, 7, 8, 5, 6],
+                18216,
+            ),
+        ];
+
+        for (program, order, signal) in examples {
+            let result = best(Network::ring(5), program, &[5, 6, 7, 8, 9]);
+            assert_eq!(result, (order.to_vec(), signal));
+        }
+    }
+
+    #[test]
+    fn custom_networks() {
+        assert_eq!("0>1,1>2".parse::<Network>().unwrap(), Network::chain(3));
+        assert_eq!("0>1,1>2,2>0".parse::<Network>().unwrap(), Network::ring(3));
+
+        // nothing sends a signal to amplifier 1, so it waits forever
+        let network = "0>2,1>2".parse::<Network>().unwrap();
+        let program = parse_program("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0").unwrap();
+        let err = network.run(&program, &[0, 1, 2], 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "network is blocked, amplifiers 1 wait for a signal"
+        );
+    }
 }
@@ -1,19 +1,31 @@
 use crate::common::*;
+use crate::image::{exporter, Image};
+use crate::ocr;
+use crate::solution::Solution;
 
 fn count(slice: &[char], c: char) -> usize {
     slice.iter().map(|&a| (a == c) as usize).sum()
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
+pub fn run(input: &str, _args: &[&str]) -> Result {
     let (width, height) = (25, 6);
-    let buffer = read_input("day08")?[0].trim().chars().collect::<Vec<_>>();
+    let buffer = input.trim().chars().collect::<Vec<_>>();
+
+    if buffer.is_empty() || buffer.len() % (width * height) != 0 {
+        return Err(ParseError(format!(
+            "expected a positive multiple of {} pixels, found {}",
+            width * height,
+            buffer.len()
+        ))
+        .into());
+    }
 
     let layers = buffer.chunks_exact(width * height).collect::<Vec<_>>();
 
     let fewest_zeros = layers.iter().min_by_key(|img| count(img, '0')).unwrap();
 
     let answer = count(fewest_zeros, '1') * count(fewest_zeros, '2');
-    println!("answer A: {}", answer);
+    answer!(A, "{}", answer);
 
     let mut img = vec!['2'; width * height];
     for layer in layers {
@@ -24,14 +36,35 @@ pub(crate) fn run(_args: &[&str]) -> Result {
         }
     }
 
-    println!("answer B:");
+    let art = img
+        .chunks(width)
+        .map(|row| map(|&c| iff!(c == '1', '*', ' '), row).collect::<String>())
+        .join("\n");
+    info!("image:\n{}", art);
 
-    for row in img.chunks(width) {
-        for &c in row {
-            print!("{}", iff!(c == '1', '*', ' '));
-        }
-        println!();
+    let message = ocr::recognize(width, height, |x, y| img[y * width + x] == '1')?;
+    answer!(B, "{}", message);
+
+    if let Some(exporter) = exporter() {
+        let image = Image::from_bools(width, height, |x, y| img[y * width + x] == '1');
+        exporter.save("day08", &image)?;
     }
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Space Image Format"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,14 +1,35 @@
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let mut program = parse_program("day09")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let mut program = parse_program(input)?;
 
     let output = program.run(&[1])?;
-    println!("answer A: {:?}", output);
+    answer!(A, "{:?}", output);
 
     let output = program.run(&[2])?;
-    println!("answer B: {:?}", output);
+    answer!(B, "{:?}", output);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Sensor Boost"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,11 +1,9 @@
+use crate::common::math::gcd;
 use crate::common::*;
+use crate::solution::Solution;
 use float_ord::FloatOrd;
 use std::collections::{BTreeMap, BinaryHeap, HashSet};
 
-fn gcd(a: isize, b: isize) -> isize {
-    iff!(b == 0, a, gcd(b, a % b))
-}
-
 fn count_detected_astroids(x: isize, y: isize, astroids: &[(isize, isize)]) -> usize {
     let mut angles = HashSet::new();
 
@@ -13,7 +11,7 @@ fn count_detected_astroids(x: isize, y: isize, astroids: &[(isize, isize)]) -> u
         if (ax, ay) != (x, y) {
             let dx = ax - x;
             let dy = ay - y;
-            let common = gcd(dx.abs(), dy.abs());
+            let common = gcd(dx.unsigned_abs() as u64, dy.unsigned_abs() as u64) as isize;
 
             angles.insert((dx / common, dy / common));
         }
@@ -53,15 +51,20 @@ fn order_astroids_by_laser(x: isize, y: isize, astroids: &[(isize, isize)]) -> V
     output
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let mut astroids = vec![];
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let grid = Grid::parse(input, |c| match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(ParseError(format!("invalid tile {:?}", c)).into()),
+    })?;
 
-    for (y, line) in enumerate(read_input("day10")?) {
-        for (x, c) in enumerate(line.chars()) {
-            if c == '#' {
-                astroids.push((x as isize, y as isize));
-            }
-        }
+    let astroids = grid
+        .find_all(|&astroid| astroid)
+        .map(|(x, y)| (x as isize, y as isize))
+        .collect_vec();
+
+    if astroids.is_empty() {
+        return Err(ParseError("no asteroids found".to_string()).into());
     }
 
     let (x, y, count) = astroids
@@ -70,10 +73,32 @@ pub(crate) fn run(_args: &[&str]) -> Result {
         .max_by_key(|&(_, _, c)| c)
         .unwrap();
 
-    println!("answer A: {}", count);
+    answer!(A, "{}", count);
 
     let ordered = order_astroids_by_laser(x, y, &astroids);
-    println!("answer B: {:?}", ordered[199]);
+    let target = ordered.get(199).ok_or_else(|| {
+        ParseError(format!(
+            "expected at least 201 asteroids, found {}",
+            astroids.len()
+        ))
+    })?;
+    answer!(B, "{:?}", target);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        10
+    }
+
+    fn title(&self) -> &'static str {
+        "Monitoring Station"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,49 +1,54 @@
+use crate::common::geom::{Dir4, Point2, YAxis};
 use crate::common::*;
+use crate::image::{exporter, Image};
 use crate::intcode::*;
+use crate::ocr;
+use crate::render::{self, bounds, Color, Glyph, Screen};
+use crate::solution::Solution;
 use std::collections::HashMap;
 
-#[derive(Copy, Clone, Hash, Debug)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
+type Panels = HashMap<Point2, i64>;
+
+fn hull_glyph(panels: &Panels, robot: Option<Point2>, p: Point2) -> Option<Glyph> {
+    Some(match panels.get(&p) {
+        _ if Some(p) == robot => Glyph('@', Color::Red),
+        Some(1) => Glyph('#', Color::White),
+        Some(_) => Glyph('.', Color::Gray),
+        None => return None,
+    })
 }
 
-use Direction::*;
+fn draw_hull(screen: &mut Screen, panels: &Panels, robot: Point2) {
+    let positions = panels.keys().chain(Some(&robot));
 
-impl Direction {
-    fn turn_right(self) -> Self {
-        match self {
-            North => East,
-            East => South,
-            South => West,
-            West => North,
-        }
-    }
+    screen.draw_sparse(positions.map(|p| (p.x, p.y)), |x, y| {
+        hull_glyph(panels, Some(robot), Point2::new(x, y))
+    });
+}
 
-    fn turn_left(self) -> Self {
-        self.turn_right().turn_right().turn_right()
-    }
+/// Returns the width and height of the smallest rectangle containing all white panels, and
+/// whether the panel at a given position within that rectangle is white.
+fn white_panels(panels: &Panels) -> (usize, usize, impl Fn(usize, usize) -> bool + '_) {
+    let white = panels.iter().filter(|(_, &c)| c == 1);
+    let positions = white.map(|(p, _)| (p.x, p.y));
+    let (min_x, min_y, width, height) = bounds(positions).unwrap_or((0, 0, 0, 0));
 
-    fn delta(self) -> (isize, isize) {
-        match self {
-            North => (0, -1),
-            East => (1, 0),
-            South => (0, 1),
-            West => (-1, 0),
-        }
-    }
+    let is_white = move |x: usize, y: usize| {
+        let p = Point2::new(x as i64 + min_x, y as i64 + min_y);
+        panels.get(&p) == Some(&1)
+    };
+
+    (width, height, is_white)
 }
 
-fn paint(mut program: Program, start_tile: i64) -> Result<HashMap<(isize, isize), i64>> {
-    let (mut x, mut y) = (0, 0);
-    let mut direction = North;
+fn paint(mut program: Program, start_tile: i64, screen: &mut Option<Screen>) -> Result<Panels> {
+    let mut pos = Point2::ORIGIN;
+    let mut direction = Dir4::Up;
     let mut panels = HashMap::new();
-    panels.insert((x, y), start_tile);
+    panels.insert(pos, start_tile);
 
     loop {
-        let color = panels.get(&(x, y)).copied().unwrap_or(0);
+        let color = panels.get(&pos).copied().unwrap_or(0);
 
         let new_color = match program.resume(Some(color))? {
             ExecState::Halted => break,
@@ -51,7 +56,7 @@ fn paint(mut program: Program, start_tile: i64) -> Result<HashMap<(isize, isize)
             ExecState::Input => return Err(ExecError::InputExhausted.into()),
         };
 
-        panels.insert((x, y), new_color);
+        panels.insert(pos, new_color);
 
         let turn = match program.resume(None)? {
             ExecState::Halted => break,
@@ -64,31 +69,72 @@ fn paint(mut program: Program, start_tile: i64) -> Result<HashMap<(isize, isize)
             _ => direction.turn_right(),
         };
 
-        let (dx, dy) = direction.delta();
-        x += dx;
-        y += dy;
+        pos += direction.delta(YAxis::Down);
+
+        if let Some(screen) = screen.as_mut().filter(|s| s.due()) {
+            draw_hull(screen, &panels, pos);
+        }
+    }
+
+    if let Some(screen) = screen {
+        draw_hull(screen, &panels, pos);
     }
 
     Ok(panels)
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let program = parse_program("day11")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let program = parse_program(input)?;
+    let mut screen = render::screen();
 
-    let panels = paint(program.clone(), 0)?;
-    println!("answer A: {:?}", panels.len());
+    let panels = paint(program.clone(), 0, &mut screen)?;
+    answer!(A, "{:?}", panels.len());
 
-    let panels = paint(program, 1)?;
-    let (min_x, max_x) = (-50, 50);
-    let (min_y, max_y) = (-10, 10);
+    if let Some(exporter) = exporter() {
+        let positions = panels.keys().map(|p| (p.x, p.y));
+        let image = Image::from_sparse_glyphs(positions, |x, y| {
+            hull_glyph(&panels, None, Point2::new(x, y))
+        });
+        exporter.save("day11-a", &image)?;
+    }
 
-    println!("answer B:");
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            print!("{}", iff!(panels.get(&(x, y)) == Some(&1), '#', ' '));
-        }
-        println!();
+    let panels = paint(program, 1, &mut screen)?;
+    let (width, height, is_white) = white_panels(&panels);
+
+    let art = (0..height)
+        .map(|y| map(|x| iff!(is_white(x, y), '#', ' '), 0..width).collect::<String>())
+        .join("\n");
+    info!("registration identifier:\n{}", art);
+
+    answer!(B, "{}", ocr::recognize(width, height, &is_white)?);
+
+    if let Some(exporter) = exporter() {
+        exporter.save("day11-b", &Image::from_bools(width, height, is_white))?;
     }
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Space Police"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn animated(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
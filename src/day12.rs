@@ -1,16 +1,8 @@
+use crate::common::cycles::find_cycle_brent;
+use crate::common::math::lcm;
+use crate::common::parse::lines;
 use crate::common::*;
-use std::collections::HashMap;
-
-fn gcd(a: i64, b: i64) -> i64 {
-    match (a, b) {
-        (a, 0) => a,
-        (a, b) => gcd(b, a % b),
-    }
-}
-
-fn lcd(a: i64, b: i64) -> i64 {
-    (a * b) / gcd(a, b)
-}
+use crate::solution::Solution;
 
 fn iterate_timestep(pos: &mut [[i64; 3]], vel: &mut [[i64; 3]]) {
     assert_eq!(pos.len(), vel.len());
@@ -39,6 +31,18 @@ fn iterate_timestep(pos: &mut [[i64; 3]], vel: &mut [[i64; 3]]) {
     }
 }
 
+/// Performs one timestep along a single axis, where every planet is given as (position, velocity).
+fn iterate_axis(state: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    map(
+        |&(p, v)| {
+            let v = v + sum(state.iter().map(|&(q, _)| (q - p).signum()));
+            (p + v, v)
+        },
+        state,
+    )
+    .collect()
+}
+
 fn calculate_energy(pos: &[[i64; 3]], vel: &[[i64; 3]]) -> i64 {
     let mut energy = 0;
 
@@ -49,19 +53,14 @@ fn calculate_energy(pos: &[[i64; 3]], vel: &[[i64; 3]]) -> i64 {
     energy
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
+pub fn run(input: &str, _args: &[&str]) -> Result {
     let pattern = regex::Regex::new("<x=(-?[0-9]+), y=(-?[0-9]+), z=(-?[0-9]+)>")?;
     let mut pos = vec![];
     let mut vel = vec![];
 
-    for line in read_input("day12")? {
-        let m = pattern.captures(&line).unwrap();
-
-        pos.push([
-            m[1].parse::<i64>().unwrap(),
-            m[2].parse::<i64>().unwrap(),
-            m[3].parse::<i64>().unwrap(),
-        ]);
+    for line in lines(input) {
+        let m = line.captures(&pattern)?;
+        pos.push([m.parse(1)?, m.parse(2)?, m.parse(3)?]);
 
         vel.push([0, 0, 0]);
     }
@@ -71,37 +70,41 @@ pub(crate) fn run(_args: &[&str]) -> Result {
         for _ in 0..1000 {
             iterate_timestep(&mut pos, &mut vel);
         }
-        println!("answer A: {}", calculate_energy(&pos, &vel));
+        answer!(A, "{}", calculate_energy(&pos, &vel));
     }
 
     // find the cycles length along each axis
     let mut cycle_length = [0; 3];
     for k in 0..3 {
-        let mut states = HashMap::new();
-        let (mut pos, mut vel) = (pos.clone(), vel.clone());
-
-        for curr in 0.. {
-            // state is vector of coordinate along k-th axis for each planet
-            let state = zip(&pos, &vel)
-                .map(|(p, v)| (p[k], v[k]))
-                .collect::<Vec<_>>();
-
-            // if state has already been seen, we have hit a cycle
-            if let Some(prev) = states.insert(state, curr) {
-                cycle_length[k] = curr - prev;
-                println!("cycle along {}-th axis: {}", k, curr - prev);
-                break;
-            }
+        // state is vector of coordinate along k-th axis for each planet
+        let state = zip(&pos, &vel).map(|(p, v)| (p[k], v[k])).collect_vec();
+        let cycle = find_cycle_brent(state, |state| iterate_axis(state));
 
-            iterate_timestep(&mut pos, &mut vel);
-        }
+        info!("cycle along {}-th axis: {}", k, cycle.length);
+        cycle_length[k] = cycle.length as u64;
     }
 
     let mut cycle = 1;
-    cycle = lcd(cycle, cycle_length[0]);
-    cycle = lcd(cycle, cycle_length[1]);
-    cycle = lcd(cycle, cycle_length[2]);
-    println!("answer B: {:?}", cycle);
+    for &length in &cycle_length {
+        cycle = lcm(cycle, length).context("cycle length overflows u64")?;
+    }
+    answer!(B, "{:?}", cycle);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        12
+    }
+
+    fn title(&self) -> &'static str {
+        "The N-Body Problem"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
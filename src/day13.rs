@@ -1,9 +1,9 @@
 use crate::common::*;
+use crate::image::{exporter, Image};
 use crate::intcode::*;
-use ndarray::prelude::*;
+use crate::render::{self, Color, Glyph, Screen};
+use crate::solution::Solution;
 use std::cmp::Ordering;
-use std::thread::sleep;
-use std::time::Duration;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum Tile {
@@ -20,7 +20,7 @@ use Tile::*;
 fn run_timestep(
     input: i64,
     program: &mut Program,
-    board: &mut Array2<Tile>,
+    board: &mut Grid<Tile>,
     score: &mut i64,
 ) -> Result {
     let mut input = Some(input).into_iter();
@@ -43,7 +43,10 @@ fn run_timestep(
                 _ => Unknown,
             };
 
-            board[(x as usize, y as usize)] = tile;
+            match board.get_mut((x as usize, y as usize)) {
+                Some(t) => *t = tile,
+                None => bail!("tile ({}, {}) is outside of the screen", x, y),
+            }
         } else {
             *score = id;
         }
@@ -52,38 +55,34 @@ fn run_timestep(
     Ok(())
 }
 
-fn count_block_tiles(board: &Array2<Tile>) -> usize {
-    board.iter().filter(|&&v| v == Block).count()
+fn count_block_tiles(board: &Grid<Tile>) -> usize {
+    board.count(|&t| t == Block)
 }
 
-fn find_tile(board: &Array2<Tile>, tile: Tile) -> Option<(usize, usize)> {
-    board
-        .indexed_iter()
-        .filter(move |(_, &t)| t == tile)
-        .map(|(idx, _)| idx)
-        .next()
+fn find_tile(board: &Grid<Tile>, tile: Tile) -> Option<(usize, usize)> {
+    board.find(|&t| t == tile)
 }
 
-fn print_board(board: &Array2<Tile>) {
-    for vec in board.gencolumns() {
-        for val in vec {
-            let c = match val {
-                Block => '=',
-                Wall => '#',
-                Paddle => '-',
-                Ball => 'O',
-                _ => ' ',
-            };
-
-            print!("{}", c);
-        }
-        println!();
+fn tile_glyph(tile: Tile) -> Glyph {
+    match tile {
+        Block => Glyph('=', Color::Yellow),
+        Wall => Glyph('#', Color::Gray),
+        Paddle => Glyph('-', Color::Cyan),
+        Ball => Glyph('O', Color::Red),
+        _ => Glyph(' ', Color::Default),
     }
 }
 
-pub(crate) fn run(args: &[&str]) -> Result {
-    let mut program = parse_program("day13")?;
-    let mut board = Array2::from_elem((50, 25), Unknown);
+fn draw_board(screen: &mut Screen, board: &Grid<Tile>) {
+    screen.draw(board.width(), board.height(), |x, y| {
+        tile_glyph(board[(x, y)])
+    });
+}
+
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let mut screen = render::screen();
+    let mut program = parse_program(input)?;
+    let mut board = Grid::new(50, 25, Unknown);
     let mut score = 0;
 
     // insert quarter
@@ -91,14 +90,18 @@ pub(crate) fn run(args: &[&str]) -> Result {
 
     // Run game once and count number of blocks
     run_timestep(0, &mut program, &mut board, &mut score)?;
-    println!("answer A: {}", count_block_tiles(&board));
+    answer!(A, "{}", count_block_tiles(&board));
+
+    if let Some(exporter) = exporter() {
+        let (width, height) = (board.width(), board.height());
+        let image = Image::from_glyphs(width, height, |x, y| tile_glyph(board[(x, y)]));
+        exporter.save("day13", &image)?;
+    }
 
     // Run game until all blocks are gone
     while count_block_tiles(&board) > 0 {
-        // Print visualization if argument given
-        if !args.is_empty() {
-            print_board(&board);
-            sleep(Duration::from_millis(50));
+        if let Some(screen) = &mut screen {
+            draw_board(screen, &board);
         }
 
         // Get ball and paddle location
@@ -115,7 +118,31 @@ pub(crate) fn run(args: &[&str]) -> Result {
         run_timestep(input, &mut program, &mut board, &mut score)?;
     }
 
-    println!("answer B: {}", score);
+    answer!(B, "{}", score);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Care Package"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn animated(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,4 +1,6 @@
+use crate::common::parse::lines;
 use crate::common::*;
+use crate::solution::Solution;
 use std::collections::HashMap;
 
 #[derive(Clone, Default, Debug)]
@@ -7,26 +9,23 @@ struct Reaction {
     output: (String, i64),
 }
 
-fn parse_input() -> Result<Vec<Reaction>> {
+fn parse_input(input: &str) -> Result<Vec<Reaction>> {
     let mut reactions = vec![];
 
-    fn parse_element(line: &str) -> Result<(String, i64)> {
-        let parts = line.split_whitespace().collect::<Vec<_>>();
-        let count = parts[0].parse()?;
-        let name = parts[1].to_string();
-
-        Ok((name, count))
+    fn parse_element(element: Span) -> Result<(String, i64)> {
+        let [count, name] = element.trim().words()?;
+        Ok((name.to_string(), count.parse()?))
     }
 
-    for line in read_input("day14")? {
-        let parts = line.splitn(2, " => ").collect::<Vec<_>>();
+    for line in lines(input) {
+        let (inputs, output) = line.split_once(" => ")?;
         let mut reaction: Reaction = default();
 
-        for input in parts[0].split(',') {
+        for input in inputs.split(",") {
             reaction.input.push(parse_element(input)?);
         }
 
-        reaction.output = parse_element(parts[1])?;
+        reaction.output = parse_element(output)?;
         reactions.push(reaction);
     }
 
@@ -74,7 +73,7 @@ fn find_ore_for_fuel(reactions: &[Reaction], fuel: i64) -> i64 {
         }
     }
 
-    pending["ORE"]
+    pending.get("ORE").copied().unwrap_or(0)
 }
 
 fn find_fuel_for_ore(reactions: &[Reaction], ore: i64) -> i64 {
@@ -95,15 +94,32 @@ fn find_fuel_for_ore(reactions: &[Reaction], ore: i64) -> i64 {
     lbnd
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let mut reactions = parse_input()?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let mut reactions = parse_input(input)?;
+    if !any(&reactions, |r| r.output.0 == "FUEL") {
+        return Err(ParseError("no reaction produces FUEL".to_string()).into());
+    }
+
     toposort(&mut reactions);
 
-    println!("answer A: {}", find_ore_for_fuel(&reactions, 1));
-    println!(
-        "answer B: {}",
-        find_fuel_for_ore(&reactions, 1_000_000_000_000)
-    );
+    answer!(A, "{}", find_ore_for_fuel(&reactions, 1));
+    answer!(B, "{}", find_fuel_for_ore(&reactions, 1_000_000_000_000));
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        14
+    }
+
+    fn title(&self) -> &'static str {
+        "Space Stoichiometry"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
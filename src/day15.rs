@@ -1,7 +1,12 @@
+use crate::common::geom::{Dir4, YAxis};
+use crate::common::search::bfs;
 use crate::common::*;
+use crate::image::{exporter, Image};
 use crate::intcode::*;
-use ndarray::prelude::*;
+use crate::render::{self, Color, Glyph, Screen};
+use crate::solution::Solution;
 use std::collections::VecDeque;
+use std::fmt;
 use std::mem::replace;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -12,50 +17,72 @@ enum Tile {
     Empty,
 }
 
-#[derive(Clone, Copy, Debug)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+use Tile::*;
+
+/// Returns the movement command for the repair droid.
+fn command(dir: Dir4) -> i64 {
+    match dir {
+        Dir4::Up => 1,
+        Dir4::Down => 2,
+        Dir4::Left => 3,
+        Dir4::Right => 4,
+    }
 }
 
-use Direction::*;
-use Tile::*;
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = match self {
+            Unknown => '?',
+            Wall => '#',
+            Empty => ' ',
+            Goal => '!',
+        };
 
-impl Direction {
-    fn command(self) -> i64 {
-        match self {
-            North => 1,
-            South => 2,
-            West => 3,
-            East => 4,
-        }
+        write!(f, "{}", c)
     }
+}
 
-    fn apply_delta(self, (x, y): (usize, usize)) -> (usize, usize) {
-        match self {
-            North => (x, y - 1),
-            South => (x, y + 1),
-            West => (x - 1, y),
-            East => (x + 1, y),
-        }
+fn tile_glyph(grid: &Grid<Tile>, droid: (usize, usize), (x, y): (usize, usize)) -> Glyph {
+    match grid[(x, y)] {
+        _ if (x, y) == droid => Glyph('D', Color::Red),
+        Unknown => Glyph(' ', Color::Default),
+        Wall => Glyph('#', Color::Gray),
+        Empty => Glyph('.', Color::Blue),
+        Goal => Glyph('!', Color::Green),
     }
 }
 
-fn build_grid(dim: usize, start: (usize, usize), program: Program) -> Result<Array2<Tile>> {
-    let mut grid = Array2::from_elem((dim, dim), Unknown);
+fn draw_grid(screen: &mut Screen, grid: &Grid<Tile>, droid: (usize, usize)) {
+    screen.draw(grid.width(), grid.height(), |x, y| {
+        tile_glyph(grid, droid, (x, y))
+    });
+}
+
+fn build_grid(
+    dim: usize,
+    start: (usize, usize),
+    program: Program,
+    screen: &mut Option<Screen>,
+) -> Result<Grid<Tile>> {
+    let mut grid = Grid::new(dim, dim, Unknown);
     let mut queue = VecDeque::new();
 
     grid[start] = Empty;
     queue.push_back((start, program));
 
     while let Some((index, program)) = queue.pop_back() {
-        for &d in &[North, South, East, West] {
-            let index = d.apply_delta(index);
+        if let Some(screen) = screen.as_mut().filter(|s| s.due()) {
+            draw_grid(screen, &grid, index);
+        }
+
+        for &d in &Dir4::ALL {
+            let index = match grid.offset(index, d.delta(YAxis::Down).into()) {
+                Some(index) => index,
+                None => bail!("maze does not fit in a {0}x{0} grid", dim),
+            };
 
             let mut mirror = program.clone();
-            let tile = match mirror.resume(Some(d.command()))? {
+            let tile = match mirror.resume(Some(command(d)))? {
                 ExecState::Output(0) => Wall,
                 ExecState::Output(1) => Empty,
                 ExecState::Output(2) => Goal,
@@ -68,61 +95,71 @@ fn build_grid(dim: usize, start: (usize, usize), program: Program) -> Result<Arr
         }
     }
 
+    if let Some(screen) = screen {
+        draw_grid(screen, &grid, start);
+    }
+
     Ok(grid)
 }
 
-fn print_grid(grid: ArrayView2<Tile>) {
-    for col in grid.gencolumns() {
-        for v in col {
-            let c = match v {
-                Unknown => '?',
-                Wall => '#',
-                Empty => ' ',
-                Goal => '!',
-            };
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let program = parse_program(input)?;
 
-            print!("{}", c);
-        }
-        println!();
+    let dim = 50;
+    let start = ((dim / 2), (dim / 2));
+    let grid = build_grid(dim, start, program, &mut render::screen())?;
+
+    debug!("maze:\n{}", grid);
+
+    if let Some(exporter) = exporter() {
+        let (width, height) = (grid.width(), grid.height());
+        let image = Image::from_glyphs(width, height, |x, y| tile_glyph(&grid, start, (x, y)));
+        exporter.save("day15", &image)?;
     }
-}
 
-fn calculate_dist(start: (usize, usize), grid: ArrayView2<Tile>) -> Array2<i64> {
-    let mut dist = grid.map(|_| std::i64::MAX);
-    let mut queue = VecDeque::new();
-    queue.push_front((start, 0));
+    let goal = grid
+        .find(|&t| t == Goal)
+        .ok_or_else(|| format_err!("oxygen system not found"))?;
 
-    while let Some((index, d)) = queue.pop_front() {
-        for &dir in &[North, East, South, West] {
-            let index = dir.apply_delta(index);
+    let passable = |&pos: &(usize, usize)| grid[pos] != Wall;
+    let search = bfs(
+        goal,
+        |&pos| grid.neighbors4(pos).filter(passable),
+        |_| false,
+    );
 
-            if grid[index] != Wall && dist[index] > d + 1 {
-                dist[index] = d + 1;
-                queue.push_back((index, d + 1));
-            }
-        }
-    }
+    let dist = search
+        .distance(&start)
+        .ok_or_else(|| format_err!("oxygen system cannot be reached"))?;
+    answer!(A, "{}", dist);
 
-    dist
-}
+    let max_dist = search.dist.values().max();
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let program = parse_program("day15")?;
+    answer!(B, "{:?}", max_dist);
 
-    let dim = 50;
-    let start = ((dim / 2), (dim / 2));
-    let grid = build_grid(dim, start, program)?;
+    Ok(())
+}
 
-    print_grid(grid.view());
+pub struct Day;
 
-    let goal = grid.indexed_iter().find(|(_, &val)| val == Goal).unwrap().0;
+impl Solution for Day {
+    fn day(&self) -> usize {
+        15
+    }
 
-    let dist = calculate_dist(goal, grid.view());
-    println!("answer A: {}", dist[start]);
+    fn title(&self) -> &'static str {
+        "Oxygen System"
+    }
 
-    let max_dist = dist.iter().filter(|&&d| d != std::i64::MAX).max();
+    fn intcode(&self) -> bool {
+        true
+    }
 
-    println!("answer B: {:?}", max_dist);
+    fn animated(&self) -> bool {
+        true
+    }
 
-    Ok(())
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
 }
@@ -1,4 +1,5 @@
 use crate::common::*;
+use crate::solution::Solution;
 use std::cmp::min;
 
 fn presum(signal: &[i8]) -> Vec<i64> {
@@ -38,7 +39,7 @@ fn fft(signal: &[i8], times: usize, offset: usize) -> Vec<i8> {
     let mut output = signal.to_vec();
 
     for t in 0..times {
-        println!("timestep {:?}", t);
+        debug!("timestep {:?}", t);
         let presum = presum(&signal);
 
         for i in offset..n {
@@ -61,14 +62,15 @@ fn array2index(arr: &[i8]) -> usize {
     output
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let signal = read_input("day16")?[0]
-        .chars()
-        .map(|c| (c as i8) - ('0' as i8))
-        .collect::<Vec<_>>();
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let signal = map(Span::parse, Span::new(input).trim().chars()).collect::<Result<Vec<i8>>>()?;
+    if signal.len() < 8 {
+        let message = format!("expected at least 8 digits, found {}", signal.len());
+        return Err(ParseError(message).into());
+    }
 
     let result = fft(&signal, 100, 0);
-    println!("answer A: {:?}", &result[..8]);
+    answer!(A, "{:?}", &result[..8]);
 
     let mut repeated_signal = vec![];
     for _ in 0..10_000 {
@@ -76,9 +78,29 @@ pub(crate) fn run(_args: &[&str]) -> Result {
     }
 
     let index = array2index(&signal[..7]);
+    if index + 8 > repeated_signal.len() {
+        let message = format!("message offset {} is beyond the end of the signal", index);
+        return Err(ParseError(message).into());
+    }
     let result = fft(&repeated_signal, 100, index);
 
-    println!("answer B: {:?}", &result[index..index + 8]);
+    answer!(B, "{:?}", &result[index..index + 8]);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        16
+    }
+
+    fn title(&self) -> &'static str {
+        "Flawed Frequency Transmission"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,61 +1,27 @@
+use crate::common::geom::{Dir4, YAxis};
 use crate::common::*;
+use crate::image::{exporter, Image};
 use crate::intcode::*;
+use crate::render::{self, Color, Glyph, Screen};
+use crate::solution::Solution;
 use itertools::Itertools;
 
-type Grid = Vec<Vec<char>>;
-
-pub(crate) fn read_grid(program: &mut Program) -> Result<Grid> {
-    let mut grid = Vec::new();
-    let (mut x, mut y) = (0, 0);
+pub(crate) fn read_grid(program: &mut Program) -> Result<Grid<char>> {
+    let mut text = String::new();
 
     while let ExecState::Output(c) = program.resume(None)? {
-        if c == '\n' as i64 {
-            x = 0;
-            y += 1;
-        } else {
-            if x >= grid.len() {
-                grid.push(vec![]);
-            }
-
-            if y >= grid[x].len() {
-                grid[x].push('.');
-            }
-
-            grid[x][y] = c as u8 as char;
-            x += 1;
-        }
+        text.push(c as u8 as char);
     }
 
-    Ok(grid)
+    Grid::parse(&text, Ok)
 }
 
-fn print_grid(grid: &Grid) {
-    for y in 0..grid[0].len() {
-        for x in 0..grid.len() {
-            print!("{}", grid[x][y]);
-        }
-        println!();
-    }
-}
+fn find_intersections(grid: &Grid<char>) -> Vec<(usize, usize)> {
+    let is_scaffold = |&pos: &(usize, usize)| grid[pos] == '#';
 
-fn find_intersections(grid: &Grid) -> Vec<(usize, usize)> {
-    let mut result = vec![];
-    let (w, h) = (grid.len(), grid[0].len());
-
-    for x in 1..(w - 1) {
-        for y in 1..(h - 1) {
-            if grid[x][y] == '#'
-                && grid[x][y + 1] == '#'
-                && grid[x][y - 1] == '#'
-                && grid[x - 1][y] == '#'
-                && grid[x + 1][y] == '#'
-            {
-                result.push((x, y))
-            }
-        }
-    }
-
-    result
+    grid.find_all(|&c| c == '#')
+        .filter(|&pos| grid.neighbors4(pos).filter(is_scaffold).count() == 4)
+        .collect()
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Ord, PartialOrd)]
@@ -65,73 +31,30 @@ enum Step {
     Forward,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-use Direction::*;
 use Step::*;
 
-impl Direction {
-    fn turn_right(self) -> Direction {
-        match self {
-            North => East,
-            East => South,
-            South => West,
-            West => North,
-        }
-    }
-
-    fn turn_left(self) -> Direction {
-        self.turn_right().turn_right().turn_right()
-    }
+fn find_robot(grid: &Grid<char>) -> Option<((usize, usize), Dir4)> {
+    grid.iter()
+        .filter(|(_, &c)| "^>v<".contains(c))
+        .find_map(|(pos, &c)| Some((pos, Dir4::from_char(c)?)))
 }
 
-fn find_path(grid: &Grid) -> Vec<Step> {
-    let (w, h) = (grid.len(), grid[0].len());
-    let mut robot = None;
-
-    for x in 0..w {
-        for y in 0..h {
-            if let Some(dir) = match grid[x][y] {
-                '^' => Some(North),
-                '>' => Some(East),
-                '<' => Some(West),
-                'v' => Some(South),
-                _ => None,
-            } {
-                robot = Some((x, y, dir));
-            }
-        }
-    }
-
-    let (mut x, mut y, mut dir) = robot.expect("no robot found");
+fn find_path(grid: &Grid<char>) -> Vec<Step> {
+    let (mut pos, mut dir) = find_robot(grid).expect("no robot found");
     let mut path = vec![];
 
-    let apply_delta = |x: usize, y: usize, d: Direction| -> (usize, usize) {
-        match d {
-            North => (x, y.wrapping_sub(1)),
-            East => (x + 1, y),
-            South => (x, y + 1),
-            West => (x.wrapping_sub(1), y),
-        }
-    };
-
-    let is_walkable = |x: usize, y: usize, d: Direction| -> bool {
-        let (nx, ny) = apply_delta(x, y, d);
-        nx < w && ny < h && grid[nx][ny] != '.'
+    // the robot stands on scaffold too, and rows may be padded with spaces
+    let walk = |pos: (usize, usize), d: Dir4| -> Option<(usize, usize)> {
+        grid.offset(pos, d.delta(YAxis::Down).into())
+            .filter(|&next| "#^>v<".contains(grid[next]))
     };
 
     loop {
-        if !is_walkable(x, y, dir) {
-            if is_walkable(x, y, dir.turn_left()) {
+        if walk(pos, dir).is_none() {
+            if walk(pos, dir.turn_left()).is_some() {
                 path.push(Left);
                 dir = dir.turn_left();
-            } else if is_walkable(x, y, dir.turn_right()) {
+            } else if walk(pos, dir.turn_right()).is_some() {
                 path.push(Right);
                 dir = dir.turn_right();
             } else {
@@ -140,14 +63,64 @@ fn find_path(grid: &Grid) -> Vec<Step> {
         }
 
         path.push(Forward);
-        let pos = apply_delta(x, y, dir);
-        x = pos.0;
-        y = pos.1;
+        pos = walk(pos, dir).unwrap();
     }
 
     path
 }
 
+fn scaffold_glyph(grid: &Grid<char>, visited: &Grid<bool>, pos: (usize, usize)) -> Glyph {
+    match grid[pos] {
+        '.' => Glyph('.', Color::Gray),
+        '#' if visited[pos] => Glyph('#', Color::Green),
+        '#' => Glyph('#', Color::White),
+        c => Glyph(c, Color::Red),
+    }
+}
+
+fn draw_scaffold(
+    screen: &mut Screen,
+    grid: &Grid<char>,
+    visited: &Grid<bool>,
+    robot: (usize, usize),
+) {
+    screen.draw(grid.width(), grid.height(), |x, y| {
+        iff!(
+            (x, y) == robot,
+            Glyph('@', Color::Red),
+            scaffold_glyph(grid, visited, (x, y))
+        )
+    });
+}
+
+/// Draws the robot walking along the scaffold, one frame per step.
+fn animate_path(screen: &mut Screen, grid: &Grid<char>, path: &[Step]) {
+    let (mut pos, mut dir) = match find_robot(grid) {
+        Some(robot) => robot,
+        None => return,
+    };
+    let mut visited = grid.map(|_| false);
+    visited[pos] = true;
+
+    draw_scaffold(screen, grid, &visited, pos);
+
+    for &step in path {
+        match step {
+            Left => dir = dir.turn_left(),
+            Right => dir = dir.turn_right(),
+            Forward => {
+                pos = match grid.offset(pos, dir.delta(YAxis::Down).into()) {
+                    Some(pos) => pos,
+                    None => return,
+                };
+                visited[pos] = true;
+
+                draw_scaffold(screen, grid, &visited, pos);
+            }
+        }
+    }
+}
+
 fn path2command(path: &[Step]) -> String {
     let mut output = String::new();
     let mut n = 0;
@@ -233,12 +206,8 @@ fn find_routines<'a>(
 fn send_commands(program: &mut Program, trace: &[usize], routines: &[&[Step]]) -> Result<i64> {
     let mut buffer = String::new();
 
-    let main = trace
-        .iter()
-        .copied()
-        .map(|i| ['A', 'B', 'C'][i])
-        .intersperse(',');
-    buffer.extend(main);
+    let main = trace.iter().copied().map(|i| ['A', 'B', 'C'][i]);
+    buffer.extend(Itertools::intersperse(main, ','));
     buffer.push('\n');
 
     for routine in routines {
@@ -250,43 +219,80 @@ fn send_commands(program: &mut Program, trace: &[usize], routines: &[&[Step]]) -
 
     let mut input = buffer.chars().map(|c| c as i64);
     let mut output = -1;
+    let mut text = String::new();
 
     while let ExecState::Output(c) = program.resume(&mut input)? {
         if c < 128 {
-            print!("{}", c as u8 as char);
+            text.push(c as u8 as char);
         } else {
             output = c;
             break;
         }
     }
 
-    println!();
+    debug!("program output:\n{}", text);
     Ok(output)
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let mut program = parse_program("day17")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let mut program = parse_program(input)?;
     let grid = read_grid(&mut program)?;
-    print_grid(&grid);
+    debug!("scaffold:\n{}", grid);
+
+    if let Some(exporter) = exporter() {
+        let visited = grid.map(|_| false);
+        let image = Image::from_glyphs(grid.width(), grid.height(), |x, y| {
+            scaffold_glyph(&grid, &visited, (x, y))
+        });
+        exporter.save("day17", &image)?;
+    }
 
     let alignments = map(|(x, y)| x * y, find_intersections(&grid));
-    println!("answer A: {}", sum(alignments));
+    answer!(A, "{}", sum(alignments));
 
     let path = find_path(&grid);
 
+    if let Some(screen) = &mut render::screen() {
+        animate_path(screen, &grid, &path);
+    }
+
     let mut routines = vec![];
     let mut trace = vec![];
     find_routines(&path, &mut routines, &mut trace);
 
-    println!("Path: {}", path2command(&path));
-    println!("Main: {}", trace.iter().copied().join(","));
+    info!("path: {}", path2command(&path));
+    info!("main: {}", trace.iter().copied().join(","));
 
     for (index, routine) in enumerate(&routines) {
-        println!("Function {}: {}", index, path2command(routine));
+        info!("function {}: {}", index, path2command(routine));
     }
 
     let output = send_commands(&mut program, &trace, &routines)?;
-    println!("answer B: {}", output);
+    answer!(B, "{}", output);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        17
+    }
+
+    fn title(&self) -> &'static str {
+        "Set and Forget"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn animated(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,8 +1,9 @@
+use crate::common::search::{bfs, dijkstra};
 use crate::common::*;
-use binary_heap_plus::BinaryHeap;
+use crate::render::{self, Color, Glyph, Screen};
+use crate::solution::Solution;
 use itertools::Itertools;
 use ndarray::prelude::*;
-use std::collections::{HashMap, VecDeque};
 use std::fmt;
 
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
@@ -32,7 +33,7 @@ impl KeySet {
     }
 
     fn from(c: char) -> Self {
-        if c >= 'a' && c <= 'z' {
+        if c.is_ascii_lowercase() {
             let i = (c as usize) - ('a' as usize);
             let mask = 1 << i;
             Self(mask)
@@ -42,7 +43,7 @@ impl KeySet {
     }
 
     fn opens_door(self, c: char) -> bool {
-        if c >= 'A' && c <= 'Z' {
+        if c.is_ascii_uppercase() {
             let i = (c as usize) - ('A' as usize);
             let mask = 1 << i;
             self.0 & mask != 0
@@ -60,169 +61,162 @@ impl KeySet {
     }
 }
 
-fn build_distance_matrix(grid: ArrayView2<char>, characters: &[char]) -> Array2<Option<usize>> {
+fn build_distance_matrix(grid: &Grid<char>, characters: &[char]) -> Array2<Option<usize>> {
     let n = characters.len();
     let mut dists = Array2::from_elem((n, n), None);
 
     for (index, &target) in enumerate(characters) {
-        let (x, y) = grid
-            .indexed_iter()
-            .find(move |(_, &v)| v == target)
-            .expect("to find character")
-            .0;
+        let start = grid.find(|&v| v == target).expect("to find character");
 
-        let mut queue = VecDeque::new();
-        let mut visited = grid.map(|_| false);
+        // paths end at the first key, door or entrance that they reach
+        let successors = |&pos: &(usize, usize)| {
+            let open = pos == start || grid[pos] == '.';
+            grid.neighbors4(pos)
+                .filter(move |&next| open && grid[next] != '#')
+        };
 
-        queue.push_back((x, y, 0));
-        visited[[x, y]] = true;
-
-        while let Some((cx, cy, dist)) = queue.pop_front() {
-            let c = grid[[cx, cy]];
-
-            if let Some(offset) = characters.iter().position(|&v| v == c) {
+        for (pos, dist) in bfs(start, successors, |_| false).dist {
+            if let Some(offset) = characters.iter().position(|&v| v == grid[pos]) {
                 dists[[index, offset]] = Some(dist);
             }
-
-            if c != '.' && c != target {
-                continue;
-            }
-
-            for &[x, y] in &[[cx - 1, cy], [cx + 1, cy], [cx, cy - 1], [cx, cy + 1]] {
-                if !visited[[x, y]] {
-                    visited[[x, y]] = true;
-                    queue.push_back((x, y, dist + 1));
-                }
-            }
         }
     }
 
     dists
 }
 
-fn explore_grid(grid: ArrayView2<char>) -> Option<usize> {
+/// Draws the vault with the keys in `keys` picked up and their doors opened.
+fn draw_vault(screen: &mut Screen, grid: &Grid<char>, keys: KeySet) {
+    screen.draw(grid.width(), grid.height(), |x, y| match grid[(x, y)] {
+        '#' => Glyph('#', Color::Gray),
+        '.' => Glyph(' ', Color::Default),
+        c if keys.contains_key(c) || keys.opens_door(c) => Glyph(' ', Color::Default),
+        c if c.is_ascii_lowercase() => Glyph(c, Color::Yellow),
+        c if c.is_ascii_uppercase() => Glyph(c, Color::Magenta),
+        c => Glyph(
+            '@',
+            iff!(c == '@' || c.is_ascii_digit(), Color::Red, Color::White),
+        ),
+    });
+}
+
+/// Returns the keys that can be reached from node `index` with the given keys, together with
+/// the node index of the key and the distance to it.
+fn reachable_keys<'a>(
+    nodes: &'a [char],
+    dists: &'a Array2<Option<usize>>,
+    index: usize,
+    keys: KeySet,
+) -> impl Iterator<Item = (usize, KeySet, usize)> + 'a {
+    enumerate(zip(nodes, dists.row(index)))
+        .filter_map(|(next_index, (&c, &l))| Some((next_index, c, l?)))
+        .filter(move |&(_, c, _)| !c.is_ascii_uppercase() || keys.opens_door(c))
+        .map(move |(next_index, c, l)| (next_index, KeySet::union(keys, KeySet::from(c)), l))
+}
+
+fn explore_grid(grid: &Grid<char>, screen: &mut Option<Screen>) -> Option<usize> {
     let nodes = "@abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
         .chars()
         .collect_vec();
 
-    let dists = build_distance_matrix(grid.view(), &nodes);
-
-    let mut queue = VecDeque::new();
-    let mut states = HashMap::new();
-
-    states.insert((0, KeySet::new()), 0);
-    queue.push_back((0, KeySet::new()));
-
-    while let Some((index, keys)) = queue.pop_front() {
-        let d = states[&(index, keys)];
-
-        for (next_index, (&c, &l)) in enumerate(zip(&nodes, &dists.row(index))) {
-            if let Some(l) = l {
-                if (c >= 'A' && c <= 'Z') && !keys.opens_door(c) {
-                    continue;
-                }
+    let dists = build_distance_matrix(grid, &nodes);
 
-                let next_keys = KeySet::union(keys, KeySet::from(c));
-                let entry = states
-                    .entry((next_index, next_keys))
-                    .or_insert(std::usize::MAX);
-
-                if *entry > d + l {
-                    *entry = d + l;
-                    queue.push_back((next_index, next_keys));
-                }
-            }
+    let successors = |&(index, keys): &(usize, KeySet)| {
+        if let Some(screen) = screen.as_mut().filter(|s| s.due()) {
+            draw_vault(screen, grid, keys);
         }
-    }
 
-    states
-        .into_iter()
-        .filter_map(|((_, keys), dist)| iff!(keys == KeySet::all(), Some(dist), None))
-        .min()
+        reachable_keys(&nodes, &dists, index, keys)
+            .map(|(next_index, next_keys, l)| ((next_index, next_keys), l))
+    };
+
+    dijkstra((0, KeySet::new()), successors, |&(_, keys)| {
+        keys == KeySet::all()
+    })
+    .goal_distance()
 }
 
-fn explore_grid_four(grid: ArrayView2<char>) -> Option<usize> {
+fn explore_grid_four(grid: &Grid<char>, screen: &mut Option<Screen>) -> Option<usize> {
     let nodes = "0123abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
         .chars()
         .collect_vec();
 
-    let dists = build_distance_matrix(grid.view(), &nodes);
+    let dists = build_distance_matrix(grid, &nodes);
+    let mut expanded = 0;
 
-    let mut queue = BinaryHeap::new_by_key(|p: &([usize; 4], KeySet, usize)| -(p.2 as isize));
-    let mut states = HashMap::new();
-
-    let k = ([0, 1, 2, 3], KeySet::new());
-    states.insert(k, 0);
-    queue.push((k.0, k.1, 0));
-    let mut waiting = 0;
-
-    while let Some((indices, keys, d)) = queue.pop() {
-        if waiting == 0 {
-            println!("{} {} {}", d, states.len(), queue.len());
-            waiting = 1_000_000;
+    let successors = |&(indices, keys): &([usize; 4], KeySet)| {
+        if expanded % 1_000_000 == 0 {
+            debug!("{} states expanded, current keys {}", expanded, keys);
         }
-        waiting -= 1;
+        expanded += 1;
 
-        if keys == KeySet::all() {
-            return Some(d);
+        if let Some(screen) = screen.as_mut().filter(|s| s.due()) {
+            draw_vault(screen, grid, keys);
         }
 
-        for robot in 0..4 {
-            let index = indices[robot];
-
-            for (next_index, (&c, &l)) in enumerate(zip(&nodes, &dists.row(index))) {
-                if let Some(l) = l {
-                    if (c >= 'A' && c <= 'Z') && !keys.opens_door(c) {
-                        continue;
-                    }
-
-                    let next_keys = KeySet::union(keys, KeySet::from(c));
+        let (nodes, dists) = (&nodes, &dists);
+        (0..4).flat_map(move |robot| {
+            reachable_keys(nodes, dists, indices[robot], keys).map(
+                move |(next_index, next_keys, l)| {
                     let mut next_indices = indices;
                     next_indices[robot] = next_index;
+                    ((next_indices, next_keys), l)
+                },
+            )
+        })
+    };
+
+    let start = ([0, 1, 2, 3], KeySet::new());
+    dijkstra(start, successors, |&(_, keys)| keys == KeySet::all()).goal_distance()
+}
 
-                    let entry = states
-                        .entry((next_indices, next_keys))
-                        .or_insert(std::usize::MAX);
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let mut grid = Grid::parse(input, Ok)?;
 
-                    if *entry > d + l {
-                        *entry = d + l;
-                        queue.push((next_indices, next_keys, d + l));
-                    }
-                }
-            }
-        }
+    // the vault is split into four around an entrance at its center
+    let (x, y) = (grid.width() / 2, grid.height() / 2);
+    if grid.width() < 3 || grid.height() < 3 || grid[(x, y)] != '@' {
+        let message = "expected the entrance at the center of the vault".to_string();
+        return Err(ParseError(message).into());
     }
 
-    None
+    let mut screen = render::screen();
+
+    let answer = explore_grid(&grid, &mut screen);
+    answer!(A, "{:?}", answer);
+
+    grid[(x - 1, y - 1)] = '0';
+    grid[(x, y - 1)] = '#';
+    grid[(x + 1, y - 1)] = '1';
+    grid[(x - 1, y)] = '#';
+    grid[(x, y)] = '#';
+    grid[(x + 1, y)] = '#';
+    grid[(x - 1, y + 1)] = '2';
+    grid[(x, y + 1)] = '#';
+    grid[(x + 1, y + 1)] = '3';
+
+    let answer = explore_grid_four(&grid, &mut screen);
+    answer!(B, "{:?}", answer);
+
+    Ok(())
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let lines = read_input("day18")?;
-    let (w, h) = (lines.len(), lines[0].len());
-    let mut grid = Array2::from_elem((w, h), '#');
+pub struct Day;
 
-    for (x, line) in enumerate(lines) {
-        for (y, c) in enumerate(line.chars()) {
-            grid[[x, y]] = c;
-        }
+impl Solution for Day {
+    fn day(&self) -> usize {
+        18
     }
 
-    let answer = explore_grid(grid.view());
-    println!("answer A: {:?}", answer);
-
-    let (x, y) = (w / 2, h / 2);
-    grid[[x - 1, y - 1]] = '0';
-    grid[[x - 1, y]] = '#';
-    grid[[x - 1, y + 1]] = '1';
-    grid[[x, y - 1]] = '#';
-    grid[[x, y]] = '#';
-    grid[[x, y + 1]] = '#';
-    grid[[x + 1, y - 1]] = '2';
-    grid[[x + 1, y]] = '#';
-    grid[[x + 1, y + 1]] = '3';
+    fn title(&self) -> &'static str {
+        "Many-Worlds Interpretation"
+    }
 
-    let answer = explore_grid_four(grid.view());
-    println!("answer B: {:?}", answer);
+    fn animated(&self) -> bool {
+        true
+    }
 
-    Ok(())
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
 }
@@ -1,5 +1,6 @@
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
 use ndarray::prelude::*;
 
 pub(crate) fn is_affected(program: &Program, x: usize, y: usize) -> Result<bool> {
@@ -26,7 +27,7 @@ pub(crate) fn fit_ship(program: &Program) -> Result<(usize, usize)> {
     let ship_size = 100;
     let grid = scan_grid(program, dim)?;
     let mut result = (0, 0);
-    let mut min_dist = std::usize::MAX;
+    let mut min_dist = usize::MAX;
 
     for x in 0..(dim - ship_size) {
         for y in 0..(dim - ship_size) {
@@ -56,17 +57,37 @@ pub(crate) fn fit_ship(program: &Program) -> Result<(usize, usize)> {
     Ok(result)
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let program = parse_program("day19")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let program = parse_program(input)?;
 
     let count = scan_grid(&program, 50)?
         .iter()
         .map(|&x| x as usize)
         .sum::<usize>();
-    println!("answer A: {}", count);
+    answer!(A, "{}", count);
 
-    let answer = fit_ship(&program);
-    println!("answer B: {:?}", answer);
+    let answer = fit_ship(&program)?;
+    answer!(B, "{:?}", answer);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        19
+    }
+
+    fn title(&self) -> &'static str {
+        "Tractor Beam"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
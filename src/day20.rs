@@ -1,60 +1,72 @@
+use crate::common::search::bfs;
 use crate::common::*;
-use std::collections::{HashMap, HashSet, VecDeque};
+use crate::image::{exporter, Image};
+use crate::render::{self, Color, Glyph, Screen};
+use crate::solution::Solution;
+use std::collections::HashMap;
 
-type Grid = HashMap<(i32, i32), char>;
+type Pos = (usize, usize);
 
-fn read_grid() -> Result<Grid> {
-    let mut grid = Grid::new();
+fn find_portals(grid: &Grid<char>) -> HashMap<String, Vec<Pos>> {
+    let mut output: HashMap<_, Vec<_>> = HashMap::new();
+    let tile =
+        |pos: Pos, (dx, dy): (isize, isize)| grid.offset(pos, (dx, dy)).map_or(' ', |p| grid[p]);
+
+    for pos in grid.find_all(|&c| c == '.') {
+        for &(dx, dy) in &[(0, 1), (0, -1), (1, 0), (-1, 0)] {
+            let a = tile(pos, (-dx, -dy));
+            let b = tile(pos, (-2 * dx, -2 * dy));
 
-    for (x, line) in enumerate(read_input("day20")?) {
-        for (y, c) in enumerate(line.chars()) {
-            if c != ' ' {
-                grid.insert((x as i32, y as i32), c);
+            if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() {
+                let name = iff!(
+                    dx > 0 || dy > 0,
+                    format!("{}{}", a, b),
+                    format!("{}{}", b, a)
+                );
+
+                output.entry(name).or_default().push(pos);
             }
         }
     }
 
-    Ok(grid)
+    output
 }
 
-fn find_portals(grid: &Grid) -> HashMap<String, Vec<(i32, i32)>> {
-    let mut output: HashMap<_, Vec<_>> = HashMap::new();
+/// Returns whether the portal at `pos` is on the outer edge of the donut.
+fn is_outer(grid: &Grid<char>, (x, y): Pos) -> bool {
+    x <= 2 || y <= 2 || x + 3 >= grid.width() || y + 3 >= grid.height()
+}
 
-    for (&(x, y), &c) in grid {
-        if c == '.' {
-            for &(dx, dy) in &[(0, 1), (0, -1), (1, 0), (-1, 0)] {
-                let a = grid.get(&(x - dx, y - dy)).unwrap_or(&' ');
-                let b = grid.get(&(x - 2 * dx, y - 2 * dy)).unwrap_or(&' ');
-
-                if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() {
-                    let name = iff!(
-                        dx > 0 || dy > 0,
-                        format!("{}{}", a, b),
-                        format!("{}{}", b, a)
-                    );
-
-                    output.entry(name).or_default().push((x, y));
-                }
-            }
-        }
+fn maze_glyph(grid: &Grid<char>, reached: &Grid<bool>, pos: Pos, p: Pos) -> Glyph {
+    match grid[p] {
+        _ if p == pos => Glyph('@', Color::Red),
+        '#' => Glyph('#', Color::Gray),
+        '.' if reached[p] => Glyph('.', Color::Cyan),
+        '.' => Glyph('.', Color::Blue),
+        ' ' => Glyph(' ', Color::Default),
+        c => Glyph(c, Color::Magenta),
     }
-
-    output
 }
 
-fn distance_to_center((x, y): (i32, i32)) -> i32 {
-    (x - 50) * (x - 50) + (y - 50) * (y - 50)
+/// Draws the maze with the tiles in `reached` highlighted.
+fn draw_maze(screen: &mut Screen, grid: &Grid<char>, reached: &Grid<bool>, pos: Pos) {
+    screen.draw(grid.width(), grid.height(), |x, y| {
+        maze_glyph(grid, reached, pos, (x, y))
+    });
 }
 
-fn find_path_length(start: &str, end: &str, grid: &Grid, recur_space: bool) -> Option<usize> {
-    let portals = find_portals(&grid);
-    let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
-    let mut options = vec![];
+fn find_path_length(
+    start: &str,
+    end: &str,
+    grid: &Grid<char>,
+    recur_space: bool,
+    screen: &mut Option<Screen>,
+) -> Option<usize> {
+    let portals = find_portals(grid);
+    let mut reached = grid.map(|_| false);
 
-    let (sx, sy) = portals[start][0];
-    let (ex, ey) = portals[end][0];
-    queue.push_back(((sx, sy, 0), 0));
+    let start = portals[start][0];
+    let end = portals[end][0];
 
     let mut teleports = HashMap::new();
     for (_, list) in portals {
@@ -63,52 +75,89 @@ fn find_path_length(start: &str, end: &str, grid: &Grid, recur_space: bool) -> O
 
             let delta = if !recur_space {
                 0
-            } else if distance_to_center(a) < distance_to_center(b) {
-                1
-            } else {
+            } else if is_outer(grid, a) {
                 -1
+            } else {
+                1
             };
 
-            teleports.insert(a, (b.0, b.1, delta));
-            teleports.insert(b, (a.0, a.1, -delta));
+            teleports.insert(a, (b, delta));
+            teleports.insert(b, (a, -delta));
         }
     }
 
-    while let Some(((x, y, ring), dist)) = queue.pop_front() {
-        if (x, y, ring) == (ex, ey, 0) {
-            return Some(dist);
-        }
+    let successors = |&(pos, ring): &(Pos, i32)| {
+        if let Some(screen) = screen.as_mut() {
+            reached[pos] = true;
 
-        for &(dx, dy) in &[(0, 1), (0, -1), (1, 0), (-1, 0)] {
-            if grid.get(&(x + dx, y + dy)) == Some(&'.') {
-                options.push((x + dx, y + dy, ring));
+            if screen.due() {
+                draw_maze(screen, grid, &reached, pos);
             }
         }
 
-        if let Some(&(nx, ny, delta)) = teleports.get(&(x, y)) {
-            if ring + delta >= 0 {
-                options.push((nx, ny, ring + delta));
-            }
-        }
+        let steps = grid.neighbors4(pos).filter(|&next| grid[next] == '.');
+        let teleport = teleports
+            .get(&pos)
+            .filter(|&&(_, delta)| ring + delta >= 0)
+            .map(|&(next, delta)| (next, ring + delta));
 
-        for p in options.drain(..) {
-            if visited.insert(p) {
-                queue.push_back((p, dist + 1));
-            }
-        }
+        steps.map(move |next| (next, ring)).chain(teleport)
+    };
+
+    let search = bfs((start, 0), successors, |&node| node == (end, 0));
+
+    if let Some(screen) = screen {
+        draw_maze(screen, grid, &reached, end);
     }
 
-    None
+    search.goal_distance()
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let grid = read_grid()?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let grid = Grid::parse(input, Ok)?;
+
+    let portals = find_portals(&grid);
+    for name in &["AA", "ZZ"] {
+        if !portals.contains_key(*name) {
+            return Err(ParseError(format!("portal {} not found", name)).into());
+        }
+    }
+
+    if let Some(exporter) = exporter() {
+        let reached = grid.map(|_| false);
+        let image = Image::from_glyphs(grid.width(), grid.height(), |x, y| {
+            maze_glyph(&grid, &reached, (usize::MAX, usize::MAX), (x, y))
+        });
+        exporter.save("day20", &image)?;
+    }
+
+    let mut screen = render::screen();
 
-    let answer_a = find_path_length("AA", "ZZ", &grid, false);
-    println!("answer A: {:?}", answer_a);
+    let answer_a = find_path_length("AA", "ZZ", &grid, false, &mut screen);
+    answer!(A, "{:?}", answer_a);
 
-    let answer_b = find_path_length("AA", "ZZ", &grid, true);
-    println!("answer B: {:?}", answer_b);
+    let answer_b = find_path_length("AA", "ZZ", &grid, true, &mut screen);
+    answer!(B, "{:?}", answer_b);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        20
+    }
+
+    fn title(&self) -> &'static str {
+        "Donut Maze"
+    }
+
+    fn animated(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
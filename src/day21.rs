@@ -1,5 +1,6 @@
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
 use itertools::Itertools;
 
 fn launch_springdroid(mut program: Program, code: &str) -> Result<Option<i64>> {
@@ -10,23 +11,25 @@ fn launch_springdroid(mut program: Program, code: &str) -> Result<Option<i64>> {
         .interleave_shortest(cycle("\n"))
         .collect::<String>();
 
-    print!("CODE: {}", input);
+    debug!("springscript:\n{}", input);
 
     let mut iter = input.chars().map(|c| c as i64);
+    let mut text = String::new();
 
     while let ExecState::Output(c) = program.resume(&mut iter)? {
         if c < 128 {
-            print!("{}", c as u8 as char);
+            text.push(c as u8 as char);
         } else {
             return Ok(Some(c));
         }
     }
 
+    debug!("springdroid output:\n{}", text);
     Ok(None)
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let program = parse_program("day21")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let program = parse_program(input)?;
 
     // !(A && B && C) && D
     // Jump if D is true and any of [A, B, C] is false
@@ -40,7 +43,7 @@ pub(crate) fn run(_args: &[&str]) -> Result {
     ";
 
     let answer = launch_springdroid(program.clone(), code)?;
-    println!("answer A: {:?}", answer);
+    answer!(A, "{:?}", answer);
 
     // !(A && B && C) && (E || H) && D
     // Jump if D is true, any of [A, B, C] is false, E or H is true
@@ -58,7 +61,27 @@ pub(crate) fn run(_args: &[&str]) -> Result {
         RUN
     ";
     let answer = launch_springdroid(program, code)?;
-    println!("answer B: {:?}", answer);
+    answer!(B, "{:?}", answer);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        21
+    }
+
+    fn title(&self) -> &'static str {
+        "Springdroid Adventure"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,28 +1,29 @@
+use crate::common::math::ModInt;
+use crate::common::parse::lines;
 use crate::common::*;
+use crate::solution::Solution;
 
 #[derive(Debug, Clone, Copy)]
 enum Technique {
     Reverse,
-    Cut(i128),
-    Deal(i128),
+    Cut(i64),
+    Deal(i64),
 }
 
 use Technique::*;
 
-fn parse_techniques() -> Result<Vec<Technique>> {
+fn parse_techniques(input: &str) -> Result<Vec<Technique>> {
     let mut output = vec![];
 
-    for line in read_input("day22")? {
-        let t = if line == "deal into new stack" {
+    for line in lines(input) {
+        let t = if line.text == "deal into new stack" {
             Reverse
-        } else if line.starts_with("cut") {
-            let n = line[3..].trim().parse()?;
-            Cut(n)
-        } else if line.starts_with("deal with increment") {
-            let n = line[19..].trim().parse()?;
-            Deal(n)
+        } else if let Some(rest) = line.strip_prefix("cut") {
+            Cut(rest.parse()?)
+        } else if let Some(rest) = line.strip_prefix("deal with increment") {
+            Deal(rest.parse()?)
         } else {
-            bail!("invalid input line: {}", line);
+            return Err(line.error("invalid technique"));
         };
 
         output.push(t);
@@ -31,105 +32,126 @@ fn parse_techniques() -> Result<Vec<Technique>> {
     Ok(output)
 }
 
-fn apply_technique(t: Technique, index: i128, num: i128) -> i128 {
-    assert!(index >= 0 && index < num);
+fn apply_technique(t: Technique, index: ModInt) -> ModInt {
+    let num = index.modulus();
 
     match t {
-        Reverse => num - 1 - index,
-        Deal(n) => (n * index) % num,
-        Cut(n) => (index - n + num) % num,
+        Reverse => -index - ModInt::new(1, num),
+        Deal(n) => ModInt::new(n, num) * index,
+        Cut(n) => index - ModInt::new(n, num),
     }
 }
 
-// Euclidean extended algorithm. From Wikipedia
-fn mult_inverse(a: i128, n: i128) -> i128 {
-    let mut t = 0;
-    let mut r = n;
-    let mut newt = 1;
-    let mut newr = a;
-
-    while newr != 0 {
-        let q = r / newr;
-
-        let oldt = t;
-        t = newt;
-        newt = oldt - q * newt;
-
-        let oldr = r;
-        r = newr;
-        newr = oldr - q * newr
-    }
-
-    while t < 0 {
-        t += n;
-    }
-    t
+/// The linear function "factor * x + constant".
+#[derive(Debug, Clone, Copy)]
+struct Linear {
+    factor: ModInt,
+    constant: ModInt,
 }
 
-// applies "factor * x + constant" given "repeat" times
-fn repeated_apply(factor: i128, constant: i128, repeat: i128, n: i128) -> (i128, i128) {
-    // repeat == 0 so result is "1 * x + 0"
-    if repeat == 0 {
-        (1, 0)
+impl Linear {
+    fn apply(self, x: ModInt) -> ModInt {
+        self.factor * x + self.constant
     }
-    // repeat == 2k so result "f (f x + c) + c" --> "f * f * x + f * c + c"
-    else if repeat % 2 == 0 {
-        let (f, c) = repeated_apply(factor, constant, repeat / 2, n);
 
-        ((f * f).rem_euclid(n), (f * c + c).rem_euclid(n))
+    fn inverse(self) -> Option<Linear> {
+        // y = f x + c  -->  x = f^-1 y - f^-1 c
+        let factor = self.factor.inverse()?;
+        let constant = -(factor * self.constant);
+        Some(Linear { factor, constant })
     }
-    // repeat == 2k + 1
-    else {
-        let (f, c) = repeated_apply(factor, constant, repeat - 1, n);
-
-        (
-            (factor * f).rem_euclid(n),
-            (factor * c + constant).rem_euclid(n),
-        )
+
+    // applies the function "repeat" times
+    fn repeat(self, repeat: u64) -> Linear {
+        let n = self.factor.modulus();
+        let mut result = Linear {
+            factor: ModInt::new(1, n),
+            constant: ModInt::new(0, n),
+        };
+        let mut square = self;
+        let mut repeat = repeat;
+
+        // "g (f x)" where g is applied after f --> "gf * f * x + gf * fc + gc"
+        while repeat > 0 {
+            if repeat % 2 == 1 {
+                result = Linear {
+                    factor: square.factor * result.factor,
+                    constant: square.factor * result.constant + square.constant,
+                };
+            }
+
+            square = Linear {
+                factor: square.factor * square.factor,
+                constant: square.factor * square.constant + square.constant,
+            };
+            repeat /= 2;
+        }
+
+        result
     }
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let input = parse_techniques()?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let input = parse_techniques(input)?;
 
     let n = 10007;
-    let mut index = 2019;
+    let mut index = ModInt::new(2019, n);
     for &t in &input {
-        index = apply_technique(t, index, n);
+        index = apply_technique(t, index);
     }
-    println!("answer A: {:?}", index);
+    answer!(A, "{}", index);
 
     let n = 119_315_717_514_047;
     let repeats = 101_741_582_076_661;
 
-    let (mut a, mut b) = (0, 1);
+    let (mut a, mut b) = (ModInt::new(0, n), ModInt::new(1, n));
     for &t in &input {
-        a = apply_technique(t, a, n);
-        b = apply_technique(t, b, n);
+        a = apply_technique(t, a);
+        b = apply_technique(t, b);
     }
 
     // since "a == factor * 0 + constant" and "b == factor * 1 + constant"
     // we know that
     //   constant = a
     //   factor = b - constant = b - a
-    let constant = a;
-    let factor = (b - a).rem_euclid(n);
-    println!("equation: {} x + {}", factor, constant);
-
-    let inv_factor = mult_inverse(factor, n);
-    let inv_constant = (-inv_factor * constant).rem_euclid(n);
-    println!("inverse equation: {} x + {}", inv_factor, inv_constant);
+    let shuffle = Linear {
+        factor: b - a,
+        constant: a,
+    };
+    info!("equation: {} x + {}", shuffle.factor, shuffle.constant);
+
+    let inverse = shuffle
+        .inverse()
+        .ok_or_else(|| format_err!("shuffle is not invertible"))?;
+    info!(
+        "inverse equation: {} x + {}",
+        inverse.factor, inverse.constant
+    );
 
-    let (rep_factor, rep_constant) = repeated_apply(inv_factor, inv_constant, repeats, n);
-    println!(
+    let repeated = inverse.repeat(repeats);
+    info!(
         "repeated inverse equation: {} x + {}",
-        inv_factor, inv_constant
+        repeated.factor, repeated.constant
     );
 
-    let index = 2020i128;
-    let result = (rep_factor * index + rep_constant).rem_euclid(n);
-
-    println!("answer B: {:?}", result);
+    let result = repeated.apply(ModInt::new(2020, n));
+    answer!(B, "{}", result);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        22
+    }
+
+    fn title(&self) -> &'static str {
+        "Slam Shuffle"
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,9 +1,10 @@
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
 use std::mem::take;
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let program = parse_program("day23")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let program = parse_program(input)?;
 
     let mut computers = vec![];
     let mut queues = vec![];
@@ -53,8 +54,28 @@ pub(crate) fn run(_args: &[&str]) -> Result {
         }
     }
 
-    println!("answer A: {:?}", history.first());
-    println!("answer B: {:?}", history.last());
+    answer!(A, "{:?}", history.first());
+    answer!(B, "{:?}", history.last());
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        23
+    }
+
+    fn title(&self) -> &'static str {
+        "Category Six"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -1,61 +1,101 @@
+use crate::common::cycles::find_cycle;
 use crate::common::*;
+use crate::image::{exporter, Image};
+use crate::render::{self, Color, Glyph, Screen};
+use crate::solution::Solution;
 use defaultmap::DefaultHashMap;
 use std::collections::HashSet;
 
 const DIM: i32 = 5;
 const MID: i32 = 2;
-type Pos = (i32, i32);
+const LEVELS_PER_ROW: usize = 16;
 type PosLevel = (i32, i32, i32);
 
-fn parse_input(lines: &[String]) -> Result<HashSet<Pos>> {
-    let mut bugs: HashSet<Pos> = default();
+fn parse_input(input: &str) -> Result<Grid<bool>> {
+    let bugs = Grid::parse(input, |c| match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(ParseError(format!("invalid tile {:?}", c)).into()),
+    })?;
 
-    for (i, line) in enumerate(lines) {
-        for (j, c) in enumerate(line.chars()) {
-            if c == '#' {
-                bugs.insert((i as _, j as _));
-            }
-        }
+    if (bugs.width(), bugs.height()) != (DIM as usize, DIM as usize) {
+        return Err(ParseError(format!("expected a {0}x{0} grid", DIM)).into());
     }
 
     Ok(bugs)
 }
 
-fn evolve(mut bugs: HashSet<Pos>) -> HashSet<Pos> {
-    let mut counts = DefaultHashMap::<Pos, usize>::new(0);
-
-    for &(i, j) in &bugs {
-        counts[(i, j)] += 0;
+fn bug_glyph(bug: bool) -> Glyph {
+    iff!(bug, Glyph('#', Color::Green), Glyph('.', Color::Gray))
+}
 
-        for &index in &[(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)] {
-            counts[index] += 1;
-        }
-    }
+fn draw_bugs(screen: &mut Screen, bugs: &Grid<bool>) {
+    screen.draw(bugs.width(), bugs.height(), |x, y| bug_glyph(bugs[(x, y)]));
+}
 
-    for (&(i, j), &c) in counts.iter() {
-        if (i >= 0 && j >= 0 && i < DIM && j < DIM)
-            && (c == 1 || (c == 2 && !bugs.contains(&(i, j))))
-        {
-            bugs.insert((i, j));
+/// Lays out every level as a separate tile, from the outermost to the innermost level,
+/// `LEVELS_PER_ROW` tiles per row. Returns the width and height of the layout and the glyph
+/// at every position.
+fn layout_levels(bugs: &HashSet<PosLevel>) -> (usize, usize, impl Fn(usize, usize) -> Glyph + '_) {
+    let min_level = bugs.iter().map(|p| p.2).min().unwrap_or(0);
+    let max_level = bugs.iter().map(|p| p.2).max().unwrap_or(0);
+    let levels = (max_level - min_level + 1) as usize;
+    let tile = DIM as usize + 1;
+
+    let width = usize::min(levels, LEVELS_PER_ROW) * tile - 1;
+    let height = levels.div_ceil(LEVELS_PER_ROW) * tile - 1;
+
+    let glyph = move |x: usize, y: usize| {
+        let index = (y / tile) * LEVELS_PER_ROW + x / tile;
+        let (i, j) = ((y % tile) as i32, (x % tile) as i32);
+
+        if i == DIM || j == DIM || index >= levels {
+            Glyph(' ', Color::Default)
+        } else if (i, j) == (MID, MID) {
+            Glyph('?', Color::Gray)
         } else {
-            bugs.remove(&(i, j));
+            bug_glyph(bugs.contains(&(i, j, min_level + index as i32)))
         }
-    }
+    };
 
-    bugs
+    (width, height, glyph)
 }
 
-fn evolve_until_repeats(mut bugs: HashSet<Pos>) -> i32 {
-    let mut scores: HashSet<_> = default();
+fn draw_levels(screen: &mut Screen, bugs: &HashSet<PosLevel>) {
+    let (width, height, glyph) = layout_levels(bugs);
+    screen.draw(width, height, glyph);
+}
+
+fn evolve(bugs: &Grid<bool>) -> Grid<bool> {
+    Grid::from_fn(bugs.width(), bugs.height(), |pos| {
+        let count = bugs.neighbors4(pos).filter(|&p| bugs[p]).count();
+        count == 1 || (count == 2 && !bugs[pos])
+    })
+}
+
+fn biodiversity(bugs: &Grid<bool>) -> u32 {
+    enumerate(bugs.iter())
+        .filter(|(_, (_, &bug))| bug)
+        .map(|(i, _)| 1 << i)
+        .sum()
+}
 
-    loop {
-        let score = sum(map(|&(i, j)| 1 << (i * DIM + j), &bugs));
-        if !scores.insert(score) {
-            break score;
+/// Evolves the bugs until a layout appears for the second time, and returns that layout.
+fn evolve_until_repeats(bugs: Grid<bool>, screen: &mut Option<Screen>) -> Grid<bool> {
+    let cycle = find_cycle(bugs, |bugs| {
+        if let Some(screen) = screen {
+            draw_bugs(screen, bugs);
         }
 
-        bugs = evolve(bugs);
+        evolve(bugs)
+    });
+
+    if let Some(screen) = screen {
+        draw_bugs(screen, &cycle.repeated);
     }
+
+    debug!("cycle of length {} at step {}", cycle.length, cycle.start);
+    cycle.repeated
 }
 
 fn evolve_recur(mut bugs: HashSet<PosLevel>) -> HashSet<PosLevel> {
@@ -104,17 +144,58 @@ fn evolve_recur(mut bugs: HashSet<PosLevel>) -> HashSet<PosLevel> {
     bugs
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let bugs = parse_input(&read_input("day24")?)?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let bugs = parse_input(input)?;
+
+    let mut screen = render::screen();
+
+    let repeated = evolve_until_repeats(bugs.clone(), &mut screen);
+    answer!(A, "{:?}", biodiversity(&repeated));
 
-    let answer = evolve_until_repeats(bugs.clone());
-    println!("part A: {:?}", answer);
+    if let Some(exporter) = exporter() {
+        let image = Image::from_bools(repeated.width(), repeated.height(), |x, y| repeated[(x, y)]);
+        exporter.save("day24-a", &image)?;
+    }
 
-    let mut bugs = map(|(i, j)| (i, j, 0), bugs).collect();
+    let mut bugs = map(|(x, y)| (y as i32, x as i32, 0), bugs.find_all(|&b| b)).collect();
     for _ in 0..200 {
+        if let Some(screen) = &mut screen {
+            draw_levels(screen, &bugs);
+        }
+
         bugs = evolve_recur(bugs);
     }
-    println!("part B: {:?}", bugs.len());
+
+    if let Some(screen) = &mut screen {
+        draw_levels(screen, &bugs);
+    }
+
+    answer!(B, "{:?}", bugs.len());
+
+    if let Some(exporter) = exporter() {
+        let (width, height, glyph) = layout_levels(&bugs);
+        exporter.save("day24-b", &Image::from_glyphs(width, height, glyph))?;
+    }
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        24
+    }
+
+    fn title(&self) -> &'static str {
+        "Planet of Discord"
+    }
+
+    fn animated(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
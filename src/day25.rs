@@ -1,36 +1,17 @@
+use crate::common::geom::Dir4;
 use crate::common::*;
 use crate::intcode::*;
+use crate::solution::Solution;
 use std::collections::HashMap;
 use std::convert::TryInto;
 
-use Dir::*;
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-enum Dir {
-    North,
-    South,
-    West,
-    East,
-}
-
-impl Dir {
-    fn name(self) -> &'static str {
-        use Dir::*;
-        match self {
-            North => "north",
-            South => "south",
-            West => "west",
-            East => "east",
-        }
-    }
-
-    fn opposite(self) -> Self {
-        use Dir::*;
-        match self {
-            North => South,
-            South => North,
-            West => East,
-            East => West,
-        }
+/// Returns the command that moves in the given direction.
+fn command(dir: Dir4) -> &'static str {
+    match dir {
+        Dir4::Up => "north",
+        Dir4::Down => "south",
+        Dir4::Left => "west",
+        Dir4::Right => "east",
     }
 }
 
@@ -39,7 +20,7 @@ struct Room {
     name: String,
     description: String,
     items: Vec<String>,
-    doors: Vec<Dir>,
+    doors: Vec<Dir4>,
 }
 
 fn parse_room(output: &str) -> Result<Room> {
@@ -61,23 +42,21 @@ fn parse_room(output: &str) -> Result<Room> {
             loc.name = line[3..line.len() - 3].to_string();
             state = Idle;
         } else if state == Items {
-            if line.starts_with("- ") {
-                loc.items.push(line[2..].to_string());
-            } else if line == "" {
+            if let Some(item) = line.strip_prefix("- ") {
+                loc.items.push(item.to_string());
+            } else if line.is_empty() {
                 state = Idle;
             } else {
                 bail!("invalid line {:?} in state {:?}", line, state);
             }
         } else if state == Doors {
-            if line == "- north" {
-                loc.doors.push(North);
-            } else if line == "- south" {
-                loc.doors.push(South);
-            } else if line == "- west" {
-                loc.doors.push(West);
-            } else if line == "- east" {
-                loc.doors.push(East);
-            } else if line == "" {
+            let door = line
+                .strip_prefix("- ")
+                .and_then(|name| Dir4::ALL.iter().find(|&&d| command(d) == name));
+
+            if let Some(&dir) = door {
+                loc.doors.push(dir);
+            } else if line.is_empty() {
                 state = Idle;
             } else {
                 bail!("invalid line {:?} in state {:?}", line, state);
@@ -89,14 +68,14 @@ fn parse_room(output: &str) -> Result<Room> {
                 state = Items;
             } else if line == "Command?" {
                 state = Done;
-            } else if line != "" {
+            } else if !line.is_empty() {
                 if !loc.description.is_empty() {
                     loc.description.push('\n');
                 }
 
                 loc.description += line;
             }
-        } else if line != "" {
+        } else if !line.is_empty() {
             bail!("invalid line {:?} in state {:?}", line, state);
         }
     }
@@ -113,7 +92,7 @@ fn send_commands(program: &mut Program, cmds: &[&str]) -> Result<String> {
     let mut output = String::new();
 
     for cmd in cmds {
-        println!("> {}", cmd);
+        trace!("> {}", cmd);
     }
 
     loop {
@@ -137,7 +116,7 @@ fn send_commands(program: &mut Program, cmds: &[&str]) -> Result<String> {
     }
 
     for line in output.lines() {
-        println!("< {}", line);
+        trace!("< {}", line);
     }
 
     Ok(output)
@@ -147,15 +126,15 @@ fn send_command(program: &mut Program, cmd: &str) -> Result<String> {
     send_commands(program, &[cmd])
 }
 
-fn move_to(program: &mut Program, dir: Dir) -> Result<Room> {
-    parse_room(&send_command(program, dir.name())?)
+fn move_to(program: &mut Program, dir: Dir4) -> Result<Room> {
+    parse_room(&send_command(program, command(dir))?)
 }
 
 fn dfs(
     program: &mut Program,
     current: &Room,
-    path: &mut Vec<Dir>,
-    visited: &mut HashMap<String, Vec<Dir>>,
+    path: &mut Vec<Dir4>,
+    visited: &mut HashMap<String, Vec<Dir4>>,
     inventory: &mut Vec<String>,
 ) -> Result {
     const IGNORE_ITEMS: &[&str] = &[
@@ -173,7 +152,7 @@ fn dfs(
         return Ok(());
     }
 
-    println!("visited {} via {:?}", current.name, path);
+    info!("visited {} via {:?}", current.name, path);
 
     if current.name == "Security Checkpoint" {
         return Ok(());
@@ -193,7 +172,7 @@ fn dfs(
         dfs(program, &room, path, visited, inventory)?;
 
         path.pop();
-        let _= move_to(program, dir.opposite())?;
+        let _ = move_to(program, dir.reverse())?;
     }
 
     Ok(())
@@ -205,13 +184,7 @@ fn pickup_everything_and_find_security(program: &mut Program) -> Result<Vec<Stri
     let mut visited = default();
     let mut inventory = vec![];
 
-    dfs(
-        program,
-        &root,
-        &mut vec![],
-        &mut visited,
-        &mut inventory,
-    )?;
+    dfs(program, &root, &mut vec![], &mut visited, &mut inventory)?;
 
     for &dir in &visited["Security Checkpoint"] {
         let _ = move_to(program, dir);
@@ -231,7 +204,7 @@ fn crack_security(program: &mut Program, inventory: &[String]) -> Result<Room> {
             }
         }
 
-        let room = move_to(&mut program, North)?;
+        let room = move_to(&mut program, Dir4::Up)?;
         if room.name != "Security Checkpoint" {
             return Ok(room);
         }
@@ -240,10 +213,37 @@ fn crack_security(program: &mut Program, inventory: &[String]) -> Result<Room> {
     bail!("failed to crack security");
 }
 
-pub(crate) fn run(_args: &[&str]) -> Result {
-    let mut program = parse_program("day25")?;
+pub fn run(input: &str, _args: &[&str]) -> Result {
+    let mut program = parse_program(input)?;
     let inventory = pickup_everything_and_find_security(&mut program)?;
-    crack_security(&mut program, &inventory)?;
+    let room = crack_security(&mut program, &inventory)?;
+
+    let code = room
+        .description
+        .split_whitespace()
+        .find_map(|word| word.parse::<u64>().ok())
+        .ok_or_else(|| format_err!("no keypad code in {:?}", room.description))?;
+    answer!(A, "{}", code);
 
     Ok(())
 }
+
+pub struct Day;
+
+impl Solution for Day {
+    fn day(&self) -> usize {
+        25
+    }
+
+    fn title(&self) -> &'static str {
+        "Cryostasis"
+    }
+
+    fn intcode(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &str, args: &[&str]) -> Result {
+        run(input, args)
+    }
+}
@@ -0,0 +1,24 @@
+use crate::common::*;
+use crate::INPUTS;
+use std::fs;
+use std::path::Path;
+
+pub fn get(filename: &str) -> Option<&'static str> {
+    INPUTS
+        .iter()
+        .find(|(name, _)| *name == filename)
+        .map(|(_, content)| *content)
+}
+
+/// Writes every embedded input to `dir`, which is created if it does not exist.
+pub fn dump(dir: &Path) -> Result {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    for (name, content) in INPUTS {
+        let path = dir.join(name);
+        fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+        info!("wrote {}", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,358 @@
+use crate::common::*;
+use crate::render::{bounds, Color, Glyph};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+}
+
+impl From<Color> for Rgb {
+    /// The default color is the background, which is black.
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Default => Rgb::BLACK,
+            Color::Red => Rgb(230, 60, 60),
+            Color::Green => Rgb(60, 200, 80),
+            Color::Yellow => Rgb(240, 210, 60),
+            Color::Blue => Rgb(60, 100, 230),
+            Color::Magenta => Rgb(210, 70, 210),
+            Color::Cyan => Rgb(60, 210, 220),
+            Color::White => Rgb::WHITE,
+            Color::Gray => Rgb(110, 110, 110),
+        }
+    }
+}
+
+/// An image of `width` by `height` pixels, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn from_fn<F>(width: usize, height: usize, pixel: F) -> Self
+    where
+        F: Fn(usize, usize) -> Rgb,
+    {
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(pixel(x, y));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Creates a white on black image.
+    pub fn from_bools<F>(width: usize, height: usize, pixel: F) -> Self
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        Self::from_fn(width, height, |x, y| {
+            iff!(pixel(x, y), Rgb::WHITE, Rgb::BLACK)
+        })
+    }
+
+    /// Creates an image where every pixel is an index into `palette`.
+    pub fn from_palette<F>(width: usize, height: usize, palette: &[Rgb], pixel: F) -> Self
+    where
+        F: Fn(usize, usize) -> usize,
+    {
+        Self::from_fn(width, height, |x, y| palette[pixel(x, y)])
+    }
+
+    /// Creates an image from the colors of the glyphs that `Screen::draw` would draw.
+    pub fn from_glyphs<F>(width: usize, height: usize, glyph: F) -> Self
+    where
+        F: Fn(usize, usize) -> Glyph,
+    {
+        Self::from_fn(width, height, |x, y| glyph(x, y).1.into())
+    }
+
+    /// Like `from_glyphs`, but for the glyphs that `Screen::draw_sparse` would draw.
+    pub fn from_sparse_glyphs<I, F>(positions: I, glyph: F) -> Self
+    where
+        I: IntoIterator<Item = (i64, i64)>,
+        F: Fn(i64, i64) -> Option<Glyph>,
+    {
+        let (min_x, min_y, width, height) = bounds(positions).unwrap_or((0, 0, 0, 0));
+
+        Self::from_fn(width, height, |x, y| {
+            glyph(x as i64 + min_x, y as i64 + min_y).map_or(Rgb::BLACK, |g| g.1.into())
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Returns the rows of the image scaled up by `scale` as RGB bytes.
+    fn scaled_rows(&self, scale: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+        (0..self.height * scale).map(move |y| {
+            let mut row = Vec::with_capacity(3 * self.width * scale);
+
+            for x in 0..self.width * scale {
+                let Rgb(r, g, b) = self.get(x / scale, y / scale);
+                row.extend_from_slice(&[r, g, b]);
+            }
+
+            row
+        })
+    }
+
+    /// Encodes the image as a binary PPM file.
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut output = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+
+        for row in self.scaled_rows(scale) {
+            output.extend(row);
+        }
+
+        output
+    }
+
+    /// Encodes the image as a PNG file. The image data is stored uncompressed, which keeps the
+    /// encoder small at the cost of larger files.
+    pub fn to_png(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.width * scale, self.height * scale);
+
+        let mut raw = vec![];
+        for row in self.scaled_rows(scale) {
+            raw.push(0); // filter type: none
+            raw.extend(row);
+        }
+
+        let mut header = vec![];
+        header.extend_from_slice(&(width as u32).to_be_bytes());
+        header.extend_from_slice(&(height as u32).to_be_bytes());
+        header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlacing
+
+        let mut output = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut output, b"IHDR", &header);
+        png_chunk(&mut output, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut output, b"IEND", &[]);
+        output
+    }
+
+    /// Encodes the image as an SVG file with one rectangle per horizontal run of equal pixels.
+    /// Black pixels are left transparent.
+    pub fn to_svg(&self, scale: usize) -> String {
+        let mut output = String::new();
+
+        writeln!(
+            output,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\">",
+            w = self.width * scale,
+            h = self.height * scale
+        )
+        .unwrap();
+
+        for y in 0..self.height {
+            let mut x = 0;
+
+            while x < self.width {
+                let color = self.get(x, y);
+                let run = (x..self.width)
+                    .take_while(|&i| self.get(i, y) == color)
+                    .count();
+
+                if color != Rgb::BLACK {
+                    let Rgb(r, g, b) = color;
+                    writeln!(
+                        output,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+                         fill=\"#{:02x}{:02x}{:02x}\"/>",
+                        x * scale,
+                        y * scale,
+                        run * scale,
+                        scale,
+                        r,
+                        g,
+                        b
+                    )
+                    .unwrap();
+                }
+
+                x += run;
+            }
+        }
+
+        output.push_str("</svg>\n");
+        output
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in data {
+        crc ^= byte as u32;
+
+        for _ in 0..8 {
+            crc = iff!(crc & 1 != 0, (crc >> 1) ^ 0xedb8_8320, crc >> 1);
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+fn png_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = output.len();
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+
+    let crc = crc32(&output[start..]);
+    output.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut output = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+
+    if blocks.peek().is_none() {
+        output.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+
+        output.push(blocks.peek().is_none() as u8);
+        output.extend_from_slice(&len.to_le_bytes());
+        output.extend_from_slice(&(!len).to_le_bytes());
+        output.extend_from_slice(block);
+    }
+
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+    output
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Png => "png",
+            Format::Svg => "svg",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "ppm" => Format::Ppm,
+            "png" => Format::Png,
+            "svg" => Format::Svg,
+            _ => bail!("unknown image format {:?}, expected ppm, png or svg", s),
+        })
+    }
+}
+
+/// Writes images to a directory, as set by `--export`.
+#[derive(Clone, Debug)]
+pub struct Exporter {
+    dir: PathBuf,
+    format: Format,
+    scale: usize,
+}
+
+static EXPORTER: RwLock<Option<Exporter>> = RwLock::new(None);
+
+/// Makes `exporter` return an exporter writing to `dir`, with every pixel scaled up to a
+/// `scale` by `scale` square.
+pub fn enable_export<P: Into<PathBuf>>(dir: P, format: Format, scale: usize) {
+    *EXPORTER.write().unwrap() = Some(Exporter {
+        dir: dir.into(),
+        format,
+        scale: usize::max(scale, 1),
+    });
+}
+
+/// Returns an exporter if image export is enabled.
+pub fn exporter() -> Option<Exporter> {
+    EXPORTER.read().unwrap().clone()
+}
+
+impl Exporter {
+    /// Writes `image` to the file `name` in the export directory, adding the extension of the
+    /// image format.
+    pub fn save(&self, name: &str, image: &Image) -> Result {
+        let data = match self.format {
+            Format::Ppm => image.to_ppm(self.scale),
+            Format::Png => image.to_png(self.scale),
+            Format::Svg => image.to_svg(self.scale).into_bytes(),
+        };
+
+        self.write(name, self.format.extension(), &data)
+    }
+
+    /// Writes an SVG document to the file `name.svg` in the export directory. Drawings that are
+    /// not made of pixels are always written as SVG, regardless of the image format.
+    pub fn save_svg(&self, name: &str, svg: &str) -> Result {
+        self.write(name, "svg", svg.as_bytes())
+    }
+
+    /// Writes a Graphviz document to the file `name.dot` in the export directory.
+    pub fn save_dot(&self, name: &str, dot: &str) -> Result {
+        self.write(name, "dot", dot.as_bytes())
+    }
+
+    fn write(&self, name: &str, extension: &str, data: &[u8]) -> Result {
+        let path = self.dir.join(format!("{}.{}", name, extension));
+
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&path, data))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        info!("wrote {}", path.display());
+        Ok(())
+    }
+}
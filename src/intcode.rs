@@ -1,3 +1,4 @@
+use crate::common::parse::parse_list;
 use crate::common::*;
 use std::sync::Arc;
 
@@ -13,7 +14,7 @@ const OP_REBASE: i64 = 9;
 const OP_HALT: i64 = 99;
 
 #[derive(Error, Debug)]
-pub(crate) enum ExecError {
+pub enum ExecError {
     #[error("invalid opcode: {0}")]
     InvalidOpcode(i64),
 
@@ -25,30 +26,26 @@ pub(crate) enum ExecError {
 }
 
 #[derive(Debug, Clone, Copy)]
-pub(crate) enum ExecState {
+pub enum ExecState {
     Halted,
     Input,
     Output(i64),
 }
 
-pub(crate) fn parse_program(filename: &str) -> Result<Program> {
-    let program = read_input(filename)?[0]
-        .split(',')
-        .map(|s| s.parse::<i64>())
-        .collect::<Result<Vec<_>, _>>()?;
-
-    Ok(Program::new(program))
+/// Parses a program from its comma-separated source text.
+pub fn parse_program(input: &str) -> Result<Program> {
+    Ok(Program::new(parse_list(input, ",")?))
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct Program {
+pub struct Program {
     data: Arc<[i64]>,
     index: i64,
     base: i64,
 }
 
 impl Program {
-    pub(crate) fn new(data: Vec<i64>) -> Self {
+    pub fn new(data: Vec<i64>) -> Self {
         Self {
             data: data.into(),
             index: 0,
@@ -64,12 +61,12 @@ impl Program {
     }
 
     #[inline(always)]
-    pub(crate) fn get(&self, index: i64) -> Result<i64, ExecError> {
+    pub fn get(&self, index: i64) -> Result<i64, ExecError> {
         Ok(self.data.get(index as usize).copied().unwrap_or(0))
     }
 
     #[inline(always)]
-    pub(crate) fn set(&mut self, index: i64, value: i64) -> Result<(), ExecError> {
+    pub fn set(&mut self, index: i64, value: i64) -> Result<(), ExecError> {
         let (i, n) = (index as usize, self.data.len());
 
         if let (Some(data), true) = (Arc::get_mut(&mut self.data), i < n) {
@@ -84,7 +81,7 @@ impl Program {
         Ok(())
     }
 
-    pub(crate) fn run(&mut self, inputs: &[i64]) -> Result<Vec<i64>, ExecError> {
+    pub fn run(&mut self, inputs: &[i64]) -> Result<Vec<i64>, ExecError> {
         let mut output = vec![];
         let mut inputs = inputs.iter().copied();
 
@@ -103,7 +100,7 @@ impl Program {
         }
     }
 
-    pub(crate) fn resume<I: IntoIterator<Item = i64>>(
+    pub fn resume<I: IntoIterator<Item = i64>>(
         &mut self,
         inputs: I,
     ) -> Result<ExecState, ExecError> {
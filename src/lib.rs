@@ -0,0 +1,78 @@
+//! Solutions to the Advent of Code 2019 puzzles.
+//!
+//! Every day is a module with a `Day` type implementing [`Solution`], which solves the puzzle
+//! for a given input text. The Intcode virtual machine used by many of the days is available
+//! from [`intcode`], and the helpers shared by all days from [`common`].
+#![allow(clippy::many_single_char_names)]
+#![allow(clippy::needless_range_loop)]
+
+#[macro_use]
+pub mod common;
+pub mod answers;
+#[cfg(feature = "embed-inputs")]
+pub mod embedded;
+pub mod image;
+pub mod intcode;
+pub mod ocr;
+pub mod render;
+pub mod runner;
+pub mod solution;
+
+use solution::Solution;
+
+// the days are declared as plain items so that rustfmt formats them
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+/// Registers the solution and the embedded input of every day. Apart from declaring its module
+/// above, this is the only place a new day needs to be added.
+macro_rules! days {
+    ($($module:ident),* $(,)?) => {
+        static SOLUTIONS: &[&dyn Solution] = &[$(&$module::Day),*];
+
+        /// Puzzle inputs compiled into the binary by the `embed-inputs` feature. Every day reads
+        /// the input named after its module.
+        #[cfg(feature = "embed-inputs")]
+        static INPUTS: &[(&str, &str)] = &[$(
+            (stringify!($module), include_str!(concat!("inputs/", stringify!($module)))),
+        )*];
+    };
+}
+
+days!(
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+);
+
+/// Returns the solutions of all days.
+pub fn solutions() -> &'static [&'static dyn Solution] {
+    SOLUTIONS
+}
+
+/// Returns the solution of the given day, if there is one.
+pub fn find_solution(day: usize) -> Option<&'static dyn Solution> {
+    SOLUTIONS.iter().copied().find(|s| s.day() == day)
+}
@@ -1,81 +1,588 @@
-#![allow(clippy::many_single_char_names)]
-#![allow(clippy::needless_range_loop)]
-
-#[macro_use]
-mod common;
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod intcode;
-
+use advent2019::answers::{Expectations, Verdict};
+use advent2019::common::parse::{set_input_file, InputError};
+use advent2019::common::*;
+use advent2019::image::{self, Format};
+use advent2019::intcode::ExecError;
+use advent2019::render;
+use advent2019::runner::{run_parallel, Outcome};
+use advent2019::solution::Solution;
+use advent2019::{find_solution, solutions};
+use anyhow::bail;
+use std::backtrace::BacktraceStatus;
 use std::env;
+use std::num::{ParseFloatError, ParseIntError};
+use std::path::PathBuf;
+use std::process::exit;
+use std::thread;
+use std::time::{Duration, Instant};
 
-fn main() {
-    let funs = [
-        day01::run,
-        day02::run,
-        day03::run,
-        day04::run,
-        day05::run,
-        day06::run,
-        day07::run,
-        day08::run,
-        day09::run,
-        day10::run,
-        day11::run,
-        day12::run,
-        day13::run,
-        day14::run,
-        day15::run,
-        day16::run,
-        day17::run,
-        day18::run,
-        day19::run,
-        day20::run,
-        day21::run,
-        day22::run,
-        day23::run,
-        day24::run,
-        day25::run,
-    ];
+// exit codes
+const EXIT_CHECK_FAILED: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_MISSING_INPUT: i32 = 3;
+const EXIT_PARSE: i32 = 4;
+const EXIT_RUNTIME: i32 = 5;
 
-    let args: Vec<_> = env::args().collect();
-    let args: Vec<_> = args.iter().map(String::as_ref).collect();
-    let binary = args.get(0).unwrap_or(&"");
-    let day = args.get(1).unwrap_or(&"");
-    let rest = args.get(2..).unwrap_or(&[]);
-
-    if let Ok(x) = day.parse::<usize>() {
-        if x > 0 && x <= funs.len() {
-            if let Err(msg) = (funs[x - 1])(&rest) {
-                eprintln!("error occurred: {:?}", msg);
+const USAGE: &str = "\
+usage: {binary} [options] DAYS [ARGS...]
+
+DAYS is a day number, a range such as 1-10, a comma-separated list of these, or `all`.
+ARGS are passed on to each selected day. Available days are {first} to {last}.
+
+options:
+    --list           list the available days and exit
+    --check          compare the answers of the selected days against the answers file
+    --bless          record the answers of the selected days in the answers file
+    --answers FILE   answers file to use (default: answers in the input directory)
+    --input-dir DIR  directory containing the puzzle inputs (default: $ADVENT2019_INPUT_DIR,
+                     or the first existing one of ./inputs, inputs next to the executable
+                     and src/inputs in the crate)
+    --input FILE     read the input of the selected day from FILE, or stdin if FILE is `-`
+    --dump-inputs DIR
+                     write the inputs embedded in the binary to DIR and exit
+    -v, -q           show more or fewer log messages, can be repeated (e.g. -vv for debug)
+    --log FILTER     log levels per module, e.g. `info,day18=trace` (default:
+                     $ADVENT2019_LOG, or only warnings and errors)
+    --backtrace      print a backtrace of errors, if one was captured
+    --visualize      draw the animated days in the terminal while they run
+    --fps N          draw at most N frames per second with --visualize (default: 20)
+    --export DIR     write the images rendered by the selected days to DIR
+    --image-format FORMAT
+                     format of exported images: ppm, png or svg (default: png). Line
+                     drawings, such as the wires of day 3, are always svg, and graphs,
+                     such as the orbits of day 6, are always Graphviz dot
+    --scale N        size in pixels of every tile of an exported image (default: 8)
+    --jobs N         run up to N days at the same time (default: number of CPUs). The output
+                     of each day is printed once it finishes. Interactive days, and
+                     animated days with --visualize, always run on their own.
+    --time           report the wall time of every part. Days are run one at a time
+                     regardless of --jobs, so that timings can be compared
+    --bench N        run the selected days N times after a warm-up run and report the
+                     minimum, median and maximum wall time of every part. Days are run one
+                     at a time regardless of --jobs
+
+exit codes:
+    0  success
+    1  wrong or missing answers in --check mode
+    2  invalid command line arguments
+    3  a puzzle input could not be found
+    4  a puzzle input could not be parsed
+    5  any other error, such as an invalid Intcode program";
+
+#[derive(Default)]
+struct Options<'a> {
+    list: bool,
+    check: bool,
+    bless: bool,
+    answers: Option<&'a str>,
+    input_dir: Option<&'a str>,
+    input: Option<&'a str>,
+    input_text: Option<String>,
+    dump_inputs: Option<&'a str>,
+    verbosity: isize,
+    log: Option<&'a str>,
+    backtrace: bool,
+    visualize: bool,
+    fps: u32,
+    export: Option<&'a str>,
+    image_format: Option<Format>,
+    scale: usize,
+    jobs: usize,
+    time: bool,
+    bench: Option<usize>,
+    days: Vec<&'static dyn Solution>,
+    rest: Vec<&'a str>,
+}
+
+fn usage(binary: &str) -> String {
+    let first = solutions().iter().map(|s| s.day()).min().unwrap_or(0);
+    let last = solutions().iter().map(|s| s.day()).max().unwrap_or(0);
+
+    USAGE
+        .replace("{binary}", binary)
+        .replace("{first}", &first.to_string())
+        .replace("{last}", &last.to_string())
+}
+
+fn list_solutions() {
+    for solution in solutions() {
+        let mut tags = vec![];
+
+        if solution.intcode() {
+            tags.push("intcode");
+        }
+
+        if solution.interactive() {
+            tags.push("interactive");
+        }
+
+        if solution.animated() {
+            tags.push("animated");
+        }
+
+        let line = format!(
+            "{:02}  {:<36} {:<8} {}",
+            solution.day(),
+            solution.title(),
+            solution.input(),
+            tags.join(", ")
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+fn parse_days(spec: &str) -> Result<Vec<&'static dyn Solution>> {
+    if spec == "all" {
+        return Ok(solutions().to_vec());
+    }
+
+    let mut days = vec![];
+
+    for part in spec.split(',') {
+        let (first, last) = match part.find('-') {
+            Some(index) => (&part[..index], &part[index + 1..]),
+            None => (part, part),
+        };
+
+        let range = match (first.parse::<usize>(), last.parse::<usize>()) {
+            (Ok(first), Ok(last)) if first <= last => first..=last,
+            _ => bail!("invalid day {:?}", part),
+        };
+
+        for day in range {
+            match find_solution(day) {
+                Some(solution) => days.push(solution),
+                None => bail!("no solution for day {}, see --list", day),
+            }
+        }
+    }
+
+    Ok(days)
+}
+
+/// Returns whether `arg` is a short flag such as `-v`, `-vv` or `-vvv`.
+fn is_repeated_flag(arg: &str, flag: char) -> bool {
+    arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == flag)
+}
+
+fn parse_args<'a>(args: &[&'a str]) -> Result<Options<'a>> {
+    let mut opts = Options {
+        jobs: thread::available_parallelism().map_or(1, |n| n.get()),
+        fps: 20,
+        scale: 8,
+        ..default()
+    };
+    let mut iter = args.iter().copied();
+
+    while let Some(arg) = iter.next() {
+        match arg {
+            "--list" => opts.list = true,
+            "--check" => opts.check = true,
+            "--bless" => opts.bless = true,
+            "--answers" => match iter.next() {
+                Some(path) => opts.answers = Some(path),
+                None => bail!("missing argument for --answers"),
+            },
+            "--input-dir" => match iter.next() {
+                Some(dir) => opts.input_dir = Some(dir),
+                None => bail!("missing argument for --input-dir"),
+            },
+            "--input" => match iter.next() {
+                Some(path) => opts.input = Some(path),
+                None => bail!("missing argument for --input"),
+            },
+            "--dump-inputs" => match iter.next() {
+                Some(dir) => opts.dump_inputs = Some(dir),
+                None => bail!("missing argument for --dump-inputs"),
+            },
+            "--log" => match iter.next() {
+                Some(filter) => opts.log = Some(filter),
+                None => bail!("missing argument for --log"),
+            },
+            _ if is_repeated_flag(arg, 'v') => opts.verbosity += arg.len() as isize - 1,
+            _ if is_repeated_flag(arg, 'q') => opts.verbosity -= arg.len() as isize - 1,
+            "--backtrace" => opts.backtrace = true,
+            "--visualize" => opts.visualize = true,
+            "--fps" => match iter.next().map(str::parse) {
+                Some(Ok(n)) if n > 0 => opts.fps = n,
+                _ => bail!("--fps requires a positive number of frames"),
+            },
+            "--export" => match iter.next() {
+                Some(dir) => opts.export = Some(dir),
+                None => bail!("missing argument for --export"),
+            },
+            "--image-format" => match iter.next() {
+                Some(format) => opts.image_format = Some(format.parse()?),
+                None => bail!("missing argument for --image-format"),
+            },
+            "--scale" => match iter.next().map(str::parse) {
+                Some(Ok(n)) if n > 0 => opts.scale = n,
+                _ => bail!("--scale requires a positive number of pixels"),
+            },
+            "--jobs" => match iter.next().map(str::parse) {
+                Some(Ok(n)) if n > 0 => opts.jobs = n,
+                _ => bail!("--jobs requires a positive number of threads"),
+            },
+            "--time" => opts.time = true,
+            "--bench" => match iter.next().map(str::parse) {
+                Some(Ok(n)) if n > 0 => opts.bench = Some(n),
+                _ => bail!("--bench requires a positive number of runs"),
+            },
+            _ if arg.starts_with('-') => bail!("unknown option {:?}", arg),
+            _ => {
+                opts.days = parse_days(arg)?;
+                opts.rest = iter.collect();
+
+                if opts.input.is_some() && opts.days.len() != 1 {
+                    bail!("--input requires exactly one day");
+                }
+
+                return Ok(opts);
             }
+        }
+    }
+
+    if opts.dump_inputs.is_none() && !opts.list {
+        bail!("no days given");
+    }
+
+    Ok(opts)
+}
+
+fn exit_code(err: &Error) -> i32 {
+    for cause in err.chain() {
+        if cause.is::<MissingInput>() {
+            return EXIT_MISSING_INPUT;
+        }
+
+        if cause.is::<ParseError>()
+            || cause.is::<InputError>()
+            || cause.is::<ParseIntError>()
+            || cause.is::<ParseFloatError>()
+        {
+            return EXIT_PARSE;
+        }
+
+        if cause.is::<ExecError>() {
+            return EXIT_RUNTIME;
+        }
+    }
+
+    EXIT_RUNTIME
+}
+
+/// Prints the error and its causes, and returns the matching exit code.
+fn report_error(err: &Error, opts: &Options) -> i32 {
+    eprintln!("error: {}", err);
+
+    for cause in err.chain().skip(1) {
+        eprintln!("  caused by: {}", cause);
+    }
+
+    if opts.backtrace {
+        let backtrace = err.backtrace();
+
+        if backtrace.status() == BacktraceStatus::Captured {
+            eprintln!("backtrace:\n{}", backtrace);
         } else {
-            eprintln!("day must be between 1 and {}", funs.len() + 1);
+            eprintln!("no backtrace was captured");
+        }
+    }
+
+    exit_code(err)
+}
+
+fn run_solution(solution: &dyn Solution, opts: &Options) -> Result {
+    let (file, result) = match &opts.input_text {
+        Some(input) => (
+            opts.input.unwrap_or_default().to_string(),
+            solution.run(input, &opts.rest),
+        ),
+        None => (
+            solution.input(),
+            read_input(&solution.input()).and_then(|input| solution.run(&input, &opts.rest)),
+        ),
+    };
+
+    let result = result.map_err(|mut err| {
+        set_input_file(&mut err, if file == "-" { "stdin" } else { &file });
+        err
+    });
+
+    result.with_context(|| format!("day {} ({}) failed", solution.day(), solution.title()))
+}
+
+fn run_check(opts: &Options) -> Result<i32> {
+    let path = opts
+        .answers
+        .map_or_else(|| input_path("answers"), PathBuf::from);
+    let mut expected = Expectations::load(&path)?;
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    let run = |solution: &dyn Solution| run_solution(solution, opts);
+
+    run_parallel(&opts.days, opts.jobs, false, run, |solution, outcome| {
+        let day = solution.day();
+        outcome.output.print();
+
+        if let Err(e) = outcome.result {
+            println!("day {:02}: FAIL (error: {:#})", day, e);
+            failed += 1;
+            return;
+        }
+
+        if opts.bless {
+            expected.bless(day, &outcome.answers);
+
+            for answer in &outcome.answers {
+                println!("day {:02} {}: blessed", day, answer.part);
+            }
+
+            return;
+        }
+
+        for (part, verdict) in expected.check(day, &outcome.answers) {
+            println!("day {:02} {}: {}", day, part, verdict);
+
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Fail { .. } => failed += 1,
+                Verdict::Missing { .. } => missing += 1,
+            }
         }
+    });
+
+    if opts.bless {
+        expected.save(&path)?;
     } else {
-        eprintln!("usage: {} [day]", binary);
+        println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    }
+
+    Ok(if failed == 0 { 0 } else { EXIT_CHECK_FAILED })
+}
+
+#[cfg(feature = "embed-inputs")]
+fn dump_inputs(dir: &str) -> Result {
+    advent2019::embedded::dump(dir.as_ref())
+}
+
+#[cfg(not(feature = "embed-inputs"))]
+fn dump_inputs(_dir: &str) -> Result {
+    bail!("this binary was built without the `embed-inputs` feature")
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.3}", d.as_secs_f64() * 1000.0)
+}
+
+fn run_timed(opts: &Options) -> i32 {
+    let mut status = 0;
+    let mut report = vec![];
+    let mut sum = Duration::default();
+    let start = Instant::now();
+
+    let run = |solution: &dyn Solution| run_with_header(solution, opts);
+
+    // days running side by side would slow each other down
+    run_parallel(&opts.days, 1, true, run, |solution, outcome| {
+        let day = solution.day();
+        let code = print_outcome(&outcome, opts);
+        if status == 0 {
+            status = code;
+        }
+
+        for answer in outcome.answers {
+            report.push((day, answer.part, answer.elapsed));
+        }
+
+        report.push((day, "total".to_string(), outcome.elapsed));
+        sum += outcome.elapsed;
+    });
+
+    println!("{:<4} {:<6} {:>12}", "day", "part", "time (ms)");
+    for (day, part, elapsed) in report {
+        println!("{:02}   {:<6} {:>12}", day, part, format_duration(elapsed));
+    }
+
+    println!("all  {:<6} {:>12}", "total", format_duration(sum));
+    println!(
+        "all  {:<6} {:>12}",
+        "wall",
+        format_duration(start.elapsed())
+    );
+
+    status
+}
+
+fn run_bench(opts: &Options, runs: usize) -> i32 {
+    let mut status = 0;
+
+    println!("# {} runs after 1 warm-up run, times in ms", runs);
+    println!(
+        "{:<4} {:<6} {:>12} {:>12} {:>12}",
+        "day", "part", "min", "median", "max"
+    );
+
+    for &solution in &opts.days {
+        let day = solution.day();
+        let mut parts = Vec::<(String, Vec<Duration>)>::new();
+
+        for run in 0..=runs {
+            let start = Instant::now();
+            let (result, answers) = capture_answers(false, || run_solution(solution, opts));
+            let total = start.elapsed();
+
+            if let Err(err) = result {
+                let code = report_error(&err, opts);
+                if status == 0 {
+                    status = code;
+                }
+                break;
+            }
+
+            // first run is the warm-up run
+            if run == 0 {
+                continue;
+            }
+
+            let times = answers
+                .into_iter()
+                .map(|a| (a.part, a.elapsed))
+                .chain(Some(("total".to_string(), total)));
+
+            for (part, elapsed) in times {
+                match parts.iter_mut().find(|(p, _)| *p == part) {
+                    Some((_, list)) => list.push(elapsed),
+                    None => parts.push((part, vec![elapsed])),
+                }
+            }
+        }
+
+        for (part, mut times) in parts {
+            times.sort();
+
+            println!(
+                "{:02}   {:<6} {:>12} {:>12} {:>12}",
+                day,
+                part,
+                format_duration(times[0]),
+                format_duration(times[times.len() / 2]),
+                format_duration(times[times.len() - 1]),
+            );
+        }
+    }
+
+    status
+}
+
+/// Runs a day, starting its output with a header if several days are run so that their answers
+/// can be told apart.
+fn run_with_header(solution: &dyn Solution, opts: &Options) -> Result {
+    if opts.days.len() > 1 {
+        write_output(format_args!(
+            "day {:02}: {}\n",
+            solution.day(),
+            solution.title()
+        ));
+    }
+
+    run_solution(solution, opts)
+}
+
+/// Prints the buffered output and the error of a day, and returns the exit code.
+fn print_outcome(outcome: &Outcome, opts: &Options) -> i32 {
+    outcome.output.print();
+
+    match &outcome.result {
+        Ok(()) => 0,
+        Err(err) => report_error(err, opts),
+    }
+}
+
+fn run_plain(opts: &Options) -> i32 {
+    let mut status = 0;
+    let run = |solution: &dyn Solution| run_with_header(solution, opts);
+
+    run_parallel(&opts.days, opts.jobs, true, run, |_, outcome| {
+        let code = print_outcome(&outcome, opts);
+        if status == 0 {
+            status = code;
+        }
+    });
+
+    status
+}
+
+fn run(opts: &mut Options) -> Result<i32> {
+    if let Some(dir) = opts.input_dir {
+        set_input_dir(dir);
+    }
+
+    if let Some(path) = opts.input {
+        opts.input_text = Some(read_input_file(path)?);
+    }
+
+    if opts.visualize {
+        render::enable(opts.fps);
+    }
+
+    if let Some(dir) = opts.export {
+        let format = opts.image_format.unwrap_or(Format::Png);
+        image::enable_export(dir, format, opts.scale);
+    }
+
+    Ok(if opts.check || opts.bless {
+        run_check(opts)?
+    } else if let Some(runs) = opts.bench {
+        run_bench(opts, runs)
+    } else if opts.time {
+        run_timed(opts)
+    } else {
+        run_plain(opts)
+    })
+}
+
+fn main() {
+    let args: Vec<_> = env::args().collect();
+    let args: Vec<_> = args.iter().map(String::as_ref).collect();
+    let binary = args.first().copied().unwrap_or("advent2019");
+
+    let mut opts = match parse_args(args.get(1..).unwrap_or(&[])) {
+        Ok(opts) => opts,
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            eprintln!("{}", usage(binary));
+            exit(EXIT_USAGE);
+        }
+    };
+
+    if opts.backtrace && env::var_os("RUST_LIB_BACKTRACE").is_none() {
+        env::set_var("RUST_LIB_BACKTRACE", "1");
+    }
+
+    let filter = opts
+        .log
+        .map(str::to_string)
+        .or_else(|| env::var(LOG_VAR).ok());
+    if let Err(msg) = set_log_filter(filter.as_deref().unwrap_or(""), opts.verbosity) {
+        eprintln!("error: {}", msg);
+        exit(EXIT_USAGE);
+    }
+
+    if opts.list {
+        list_solutions();
+        return;
+    }
+
+    let result = if let Some(dir) = opts.dump_inputs {
+        dump_inputs(dir).map(|_| 0)
+    } else {
+        run(&mut opts)
+    };
+
+    match result {
+        Ok(0) => {}
+        Ok(status) => exit(status),
+        Err(err) => exit(report_error(&err, &opts)),
     }
 }
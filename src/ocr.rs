@@ -0,0 +1,217 @@
+use crate::common::*;
+use std::fmt;
+
+/// The letters of the font used by the puzzles, six pixels high and separated by empty columns.
+/// In the puzzles every letter takes five columns, so the letter Y touches the next one.
+const FONT: [&str; 6] = [
+    ".##..###...##..####.####..##..#..#.###...##.#..#.#.....##..###..###...###.#..#.#...#.####",
+    "#..#.#..#.#..#.#....#....#..#.#..#..#.....#.#.#..#....#..#.#..#.#..#.#....#..#.#...#....#",
+    "#..#.###..#....###..###..#....####..#.....#.##...#....#..#.#..#.#..#.#....#..#..#.#....#.",
+    "####.#..#.#....#....#....#.##.#..#..#.....#.#.#..#....#..#.###..###...##..#..#...#....#..",
+    "#..#.#..#.#..#.#....#....#..#.#..#..#..#..#.#.#..#....#..#.#....#.#.....#.#..#...#...#...",
+    "#..#.###...##..####.#.....###.#..#.###..##..#..#.####..##..#....#..#.###...##....#...####",
+];
+const LETTERS: &str = "ABCEFGHIJKLOPRSUYZ";
+const HEIGHT: usize = 6;
+
+/// The glyphs that could not be recognized, with the column at which each of them starts.
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub struct UnknownGlyphs(pub Vec<(usize, String)>);
+
+impl fmt::Display for UnknownGlyphs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unrecognized glyphs:")?;
+
+        for (column, glyph) in &self.0 {
+            write!(f, "\n  at column {}:\n{}", column, glyph)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the columns `start..end` of the image as rows of `#` and `.`.
+fn glyph<F>(rows: &[usize], start: usize, end: usize, is_set: F) -> String
+where
+    F: Fn(usize, usize) -> bool,
+{
+    rows.iter()
+        .map(|&y| map(|x| iff!(is_set(x, y), '#', '.'), start..end).collect::<String>())
+        .join("\n")
+}
+
+/// Splits the font into its letters at the empty columns. Every letter is returned together
+/// with its width and its glyph.
+fn font() -> Vec<(char, usize, String)> {
+    let rows = (0..HEIGHT).collect_vec();
+    let is_set = |x: usize, y: usize| FONT[y].as_bytes()[x] == b'#';
+    let empty = |x: usize| !any(&rows, |&y| is_set(x, y));
+
+    let mut letters = vec![];
+    let mut x = 0;
+
+    for letter in LETTERS.chars() {
+        while empty(x) {
+            x += 1;
+        }
+
+        let start = x;
+        while x < FONT[0].len() && !empty(x) {
+            x += 1;
+        }
+
+        letters.push((letter, x - start, glyph(&rows, start, x, is_set)));
+    }
+
+    // try wider letters first, since a letter such as Y touches the next one
+    letters.sort_by_key(|&(_, width, _)| std::cmp::Reverse(width));
+    letters
+}
+
+/// Reads the letters drawn in an image of `width` by `height` pixels, where `is_set` tells
+/// whether a pixel is lit. Empty rows above and below the letters are ignored.
+pub fn recognize<F>(width: usize, height: usize, is_set: F) -> Result<String>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let lit_rows = (0..height)
+        .filter(|&y| any(0..width, |x| is_set(x, y)))
+        .collect::<Vec<_>>();
+
+    let rows = match (lit_rows.first(), lit_rows.last()) {
+        (Some(&first), Some(&last)) if last - first + 1 == HEIGHT => (first..=last).collect_vec(),
+        (Some(&first), Some(&last)) => bail!(
+            "letters are {} pixels high, expected {}",
+            last - first + 1,
+            HEIGHT
+        ),
+        _ => return Ok(String::new()),
+    };
+
+    let font = font();
+    let empty = |x: usize| !any(&rows, |&y| is_set(x, y));
+    let mut output = String::new();
+    let mut unknown = vec![];
+    let mut x = 0;
+
+    while x < width {
+        if empty(x) {
+            x += 1;
+            continue;
+        }
+
+        let letter = font
+            .iter()
+            .find(|(_, w, g)| x + w <= width && *g == glyph(&rows, x, x + w, &is_set));
+
+        match letter {
+            Some(&(letter, w, _)) => {
+                output.push(letter);
+                x += w;
+            }
+            None => {
+                // skip to the next empty column
+                let start = x;
+                while x < width && !empty(x) {
+                    x += 1;
+                }
+
+                unknown.push((start, glyph(&rows, start, x, &is_set)));
+            }
+        }
+    }
+
+    if !unknown.is_empty() {
+        return Err(UnknownGlyphs(unknown).into());
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads an image drawn with `#` for lit pixels.
+    fn read(image: &[&str]) -> Result<String> {
+        let width = image.iter().map(|row| row.len()).max().unwrap_or(0);
+        recognize(width, image.len(), |x, y| {
+            image[y].as_bytes().get(x) == Some(&b'#')
+        })
+    }
+
+    /// Draws `text` the way the puzzles do, with every letter in a cell of five columns.
+    fn draw(text: &str) -> Vec<String> {
+        let font = font();
+        let mut image = vec![String::new(); HEIGHT];
+
+        for c in text.chars() {
+            let (_, _, glyph) = font.iter().find(|(letter, _, _)| *letter == c).unwrap();
+
+            for (row, line) in zip(&mut image, glyph.lines()) {
+                row.push_str(&format!("{:.<5}", line));
+            }
+        }
+
+        image
+    }
+
+    fn read_text(text: &str) -> Result<String> {
+        let image = draw(text);
+        read(&map(String::as_str, &image).collect_vec())
+    }
+
+    #[test]
+    fn every_letter() {
+        for letter in LETTERS.chars() {
+            assert_eq!(read_text(&letter.to_string()).unwrap(), letter.to_string());
+        }
+
+        assert_eq!(read_text(LETTERS).unwrap(), LETTERS);
+    }
+
+    #[test]
+    fn adjacent_letters() {
+        for text in &["YAAAA", "YY", "AY", "YZY", "ZYIY", "LYL"] {
+            assert_eq!(read_text(text).unwrap(), *text);
+        }
+    }
+
+    #[test]
+    fn puzzle_outputs() {
+        let day08 = [
+            " ##  #### #  #   ## #   #",
+            "#  #    # # #     # #   #",
+            "#      #  ##      #  # # ",
+            "# ##  #   # #     #   #  ",
+            "#  # #    # #  #  #   #  ",
+            " ### #### #  #  ##    #  ",
+        ];
+        assert_eq!(read(&day08).unwrap(), "GZKJY");
+
+        let day11 = [
+            "",
+            "###   ##  ###  ###   ##  ###  ###  #  #",
+            "#  # #  # #  # #  # #  # #  # #  # #  #",
+            "#  # #  # #  # #  # #    ###  #  # ####",
+            "###  #### ###  ###  #    #  # ###  #  #",
+            "# #  #  # #    # #  #  # #  # #    #  #",
+            "#  # #  # #    #  #  ##  ###  #    #  #",
+            "",
+        ];
+        assert_eq!(read(&day11).unwrap(), "RAPRCBPH");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(read(&[]).unwrap(), "");
+        assert!(read(&["#", "#", "#"]).is_err());
+
+        let mut image = draw("AB");
+        image[0].replace_range(0..1, "#");
+        let err = read(&map(String::as_str, &image).collect_vec()).unwrap_err();
+        let unknown = err.downcast::<UnknownGlyphs>().unwrap();
+        assert_eq!(unknown.0.len(), 1);
+        assert_eq!(unknown.0[0].0, 0);
+    }
+}
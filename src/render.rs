@@ -0,0 +1,174 @@
+use std::env;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static FPS: AtomicU32 = AtomicU32::new(20);
+
+/// Enables drawing to the terminal, showing at most `fps` frames per second.
+pub fn enable(fps: u32) {
+    ENABLED.store(true, Ordering::SeqCst);
+    FPS.store(u32::max(fps, 1), Ordering::SeqCst);
+}
+
+/// Returns whether solutions should draw to the terminal, as set by `--visualize`.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    Default,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+}
+
+impl Color {
+    fn ansi_code(self) -> u8 {
+        match self {
+            Color::Default => 39,
+            Color::Red => 91,
+            Color::Green => 92,
+            Color::Yellow => 93,
+            Color::Blue => 94,
+            Color::Magenta => 95,
+            Color::Cyan => 96,
+            Color::White => 97,
+            Color::Gray => 90,
+        }
+    }
+}
+
+/// Returns the smallest x and y coordinates of the given positions, and the width and height of
+/// the rectangle containing all of them. Returns `None` if there are no positions.
+pub fn bounds<I>(positions: I) -> Option<(i64, i64, usize, usize)>
+where
+    I: IntoIterator<Item = (i64, i64)>,
+{
+    let (mut min_x, mut min_y) = (i64::MAX, i64::MAX);
+    let (mut max_x, mut max_y) = (i64::MIN, i64::MIN);
+
+    for (x, y) in positions {
+        min_x = i64::min(min_x, x);
+        min_y = i64::min(min_y, y);
+        max_x = i64::max(max_x, x);
+        max_y = i64::max(max_y, y);
+    }
+
+    if min_x > max_x {
+        return None;
+    }
+
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+    Some((min_x, min_y, width, height))
+}
+
+/// A character drawn in the given color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Glyph(pub char, pub Color);
+
+/// Draws frames in place on the terminal. Each frame overwrites the previous one by moving the
+/// cursor back up using ANSI escape codes.
+#[derive(Debug)]
+pub struct Screen {
+    lines: usize,
+    frame_time: Duration,
+    last_frame: Option<Instant>,
+    color: bool,
+}
+
+/// Returns a screen to draw on if visualization is enabled.
+pub fn screen() -> Option<Screen> {
+    if !enabled() {
+        return None;
+    }
+
+    Some(Screen {
+        lines: 0,
+        frame_time: Duration::from_secs(1) / FPS.load(Ordering::SeqCst),
+        last_frame: None,
+        color: env::var_os("NO_COLOR").is_none(),
+    })
+}
+
+impl Screen {
+    /// Returns whether enough time has passed since the previous frame to draw the next one.
+    /// Simulations that run faster than the frame rate use this to skip frames.
+    pub fn due(&self) -> bool {
+        self.last_frame
+            .is_none_or(|t| t.elapsed() >= self.frame_time)
+    }
+
+    /// Draws a frame of `width` by `height` glyphs, waiting first if the previous frame was
+    /// drawn less than one frame time ago.
+    pub fn draw<F>(&mut self, width: usize, height: usize, glyph: F)
+    where
+        F: Fn(usize, usize) -> Glyph,
+    {
+        if let Some(last) = self.last_frame {
+            if let Some(wait) = self.frame_time.checked_sub(last.elapsed()) {
+                sleep(wait);
+            }
+        }
+
+        let mut buffer = String::new();
+
+        if self.lines > 0 {
+            write!(buffer, "\x1b[{}F", self.lines).unwrap();
+        }
+
+        for y in 0..height {
+            let mut current = Color::Default;
+
+            for x in 0..width {
+                let Glyph(c, color) = glyph(x, y);
+
+                if self.color && color != current {
+                    write!(buffer, "\x1b[{}m", color.ansi_code()).unwrap();
+                    current = color;
+                }
+
+                buffer.push(c);
+            }
+
+            if current != Color::Default {
+                buffer.push_str("\x1b[39m");
+            }
+
+            buffer.push_str("\x1b[K\n");
+        }
+
+        // clear any lines left over from a larger previous frame
+        buffer.push_str("\x1b[J");
+        out!("{}", buffer);
+
+        self.lines = height;
+        self.last_frame = Some(Instant::now());
+    }
+
+    /// Draws a frame for the given positions, which are translated such that the smallest
+    /// coordinates are at the top left. Positions for which `glyph` returns `None` are blank.
+    pub fn draw_sparse<I, F>(&mut self, positions: I, glyph: F)
+    where
+        I: IntoIterator<Item = (i64, i64)>,
+        F: Fn(i64, i64) -> Option<Glyph>,
+    {
+        let (min_x, min_y, width, height) = match bounds(positions) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        self.draw(width, height, |x, y| {
+            glyph(x as i64 + min_x, y as i64 + min_y).unwrap_or(Glyph(' ', Color::Default))
+        });
+    }
+}
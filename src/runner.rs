@@ -0,0 +1,115 @@
+use crate::common::*;
+use crate::render;
+use crate::solution::Solution;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The outcome of running the solution of one day.
+#[derive(Debug)]
+pub struct Outcome {
+    pub result: Result,
+    pub answers: Vec<Answer>,
+    /// Output written by the solution, if it was buffered.
+    pub output: Output,
+    pub elapsed: Duration,
+}
+
+/// Whether the solution must run on its own because it uses the terminal.
+fn runs_alone(solution: &dyn Solution) -> bool {
+    solution.interactive() || (solution.animated() && render::enabled())
+}
+
+fn execute<F>(solution: &dyn Solution, echo: bool, fun: &F) -> Outcome
+where
+    F: Fn(&dyn Solution) -> Result,
+{
+    let start = Instant::now();
+    let (result, answers) = capture_answers(echo, || fun(solution));
+
+    Outcome {
+        result,
+        answers,
+        output: default(),
+        elapsed: start.elapsed(),
+    }
+}
+
+fn execute_batch<F, G>(batch: &[&dyn Solution], jobs: usize, echo: bool, fun: &F, done: &mut G)
+where
+    F: Fn(&dyn Solution) -> Result + Sync,
+    G: FnMut(&dyn Solution, Outcome),
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..usize::min(jobs, batch.len()) {
+            let sender = sender.clone();
+            let next = &next;
+
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= batch.len() {
+                    break;
+                }
+
+                let (mut outcome, output) = buffer_output(|| execute(batch[index], echo, fun));
+                outcome.output = output;
+
+                if sender.send((index, outcome)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        drop(sender);
+
+        // outcomes arrive in any order, but are passed on in the order of the batch
+        let mut pending = map(|_| None, batch).collect::<Vec<_>>();
+        let mut finished = 0;
+
+        for (index, outcome) in receiver {
+            pending[index] = Some(outcome);
+
+            while let Some(outcome) = pending.get_mut(finished).and_then(Option::take) {
+                done(batch[finished], outcome);
+                finished += 1;
+            }
+        }
+    });
+}
+
+/// Calls `fun` for every solution using up to `jobs` threads, and calls `done` with the outcome
+/// of every solution in the order of `solutions`. The answers reported by the solutions are
+/// captured, and also written to the output if `echo` is true.
+///
+/// Solutions running on a worker thread have their output buffered. Interactive solutions,
+/// animated solutions while visualization is enabled, and all solutions if `jobs` is 1, run one
+/// at a time on the calling thread without buffering.
+pub fn run_parallel<F, G>(solutions: &[&dyn Solution], jobs: usize, echo: bool, fun: F, mut done: G)
+where
+    F: Fn(&dyn Solution) -> Result + Sync,
+    G: FnMut(&dyn Solution, Outcome),
+{
+    let mut index = 0;
+
+    while index < solutions.len() {
+        let solution = solutions[index];
+
+        if jobs <= 1 || runs_alone(solution) {
+            done(solution, execute(solution, echo, &fun));
+            index += 1;
+            continue;
+        }
+
+        let end = solutions[index..]
+            .iter()
+            .position(|s| runs_alone(*s))
+            .map_or(solutions.len(), |n| index + n);
+
+        execute_batch(&solutions[index..end], jobs, echo, &fun, &mut done);
+        index = end;
+    }
+}
@@ -0,0 +1,41 @@
+use crate::common::*;
+
+/// A solution to the puzzle of one day, together with some information about it.
+pub trait Solution: Sync {
+    /// Day of the puzzle, starting at 1.
+    fn day(&self) -> usize;
+
+    /// Title of the puzzle.
+    fn title(&self) -> &'static str;
+
+    /// Name of the input file in the input directory.
+    fn input(&self) -> String {
+        format!("day{:02}", self.day())
+    }
+
+    /// Whether the puzzle input is an Intcode program.
+    fn intcode(&self) -> bool {
+        false
+    }
+
+    /// Whether the solution uses the terminal directly, for example to read from stdin.
+    fn interactive(&self) -> bool {
+        false
+    }
+
+    /// Whether the solution draws to the terminal through [`crate::render`] when visualization
+    /// is enabled.
+    fn animated(&self) -> bool {
+        false
+    }
+
+    /// Solves the puzzle for the given input text and reports the answers using `answer!`.
+    /// `args` are the extra command line arguments given to the runner.
+    fn run(&self, input: &str, args: &[&str]) -> Result;
+
+    /// Solves the puzzle for the given input text and returns the answers.
+    fn solve(&self, input: &str) -> Result<Vec<Answer>> {
+        let (result, answers) = capture_answers(false, || self.run(input, &[]));
+        result.map(|_| answers)
+    }
+}